@@ -0,0 +1,52 @@
+//! Compares decoding a dense enum through its lookup table against the same
+//! variants in a wider value space, which is decoded with a `match`
+//!
+//! Run with `cargo bench --bench from_int`
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use indiscriminant::indiscriminant_bits;
+use seq_macro::seq;
+
+const ROUNDS: usize = 20_000;
+
+// Two thirds of the u8 value space, enough for a lookup table in a u8 but not
+// in a u16
+seq!(N in 0..171 {
+    #[indiscriminant_bits(u8)]
+    #[derive(Clone, Copy)]
+    enum Table {
+        #(V~N = N,)*
+    }
+
+    #[indiscriminant_bits(u16)]
+    #[derive(Clone, Copy)]
+    enum Match {
+        #(V~N = N,)*
+    }
+});
+
+fn bench(name: &str, decode: impl Fn(u8) -> Option<u16>) {
+    let start = Instant::now();
+    let mut checksum = 0u64;
+    for _ in 0..ROUNDS {
+        for value in 0..=u8::MAX {
+            if let Some(decoded) = decode(black_box(value)) {
+                checksum += decoded as u64;
+            }
+        }
+    }
+    let elapsed = start.elapsed();
+    let per_call = elapsed.as_secs_f64() * 1e9 / (ROUNDS * 256) as f64;
+    println!("{name:>6}: {elapsed:?} ({per_call:.2} ns/call, checksum {checksum})");
+}
+
+fn main() {
+    bench("table", |value| {
+        Table::from_int(value).map(|v| v.to_int() as u16)
+    });
+    bench("match", |value| {
+        Match::from_int(value as u16).map(|v| v.to_int())
+    });
+}
@@ -0,0 +1,247 @@
+//! Compares looking up a large keyword set through the generated `from_str`
+//! against a plain `match` on the same strings
+//!
+//! Run with `cargo bench --bench from_str`
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use indiscriminant::indiscriminant_str;
+
+const ROUNDS: usize = 20_000;
+
+#[indiscriminant_str()]
+#[derive(Clone, Copy)]
+enum Keyword {
+    Add = "ADD",
+    All = "ALL",
+    Alter = "ALTER",
+    And = "AND",
+    Any = "ANY",
+    As = "AS",
+    Asc = "ASC",
+    Backup = "BACKUP",
+    Between = "BETWEEN",
+    By = "BY",
+    Case = "CASE",
+    Check = "CHECK",
+    Column = "COLUMN",
+    Constraint = "CONSTRAINT",
+    Create = "CREATE",
+    Database = "DATABASE",
+    Default = "DEFAULT",
+    Delete = "DELETE",
+    Desc = "DESC",
+    Distinct = "DISTINCT",
+    Drop = "DROP",
+    Exec = "EXEC",
+    Exists = "EXISTS",
+    Foreign = "FOREIGN",
+    From = "FROM",
+    Full = "FULL",
+    Group = "GROUP",
+    Having = "HAVING",
+    In = "IN",
+    Index = "INDEX",
+    Inner = "INNER",
+    Insert = "INSERT",
+    Into = "INTO",
+    Is = "IS",
+    Join = "JOIN",
+    Key = "KEY",
+    Left = "LEFT",
+    Like = "LIKE",
+    Limit = "LIMIT",
+    Not = "NOT",
+    Null = "NULL",
+    Or = "OR",
+    Order = "ORDER",
+    Outer = "OUTER",
+    Primary = "PRIMARY",
+    Procedure = "PROCEDURE",
+    Replace = "REPLACE",
+    Right = "RIGHT",
+    Rownum = "ROWNUM",
+    Select = "SELECT",
+    Set = "SET",
+    Table = "TABLE",
+    Top = "TOP",
+    Truncate = "TRUNCATE",
+    Union = "UNION",
+    Unique = "UNIQUE",
+    Update = "UPDATE",
+    Values = "VALUES",
+    View = "VIEW",
+    Where = "WHERE",
+    With = "WITH",
+    Begin = "BEGIN",
+    Commit = "COMMIT",
+    Rollback = "ROLLBACK",
+    Savepoint = "SAVEPOINT",
+    Transaction = "TRANSACTION",
+    Grant = "GRANT",
+    Revoke = "REVOKE",
+    Cascade = "CASCADE",
+    Restrict = "RESTRICT",
+    References = "REFERENCES",
+    Trigger = "TRIGGER",
+    Function = "FUNCTION",
+    Returns = "RETURNS",
+    Return = "RETURN",
+    Declare = "DECLARE",
+    Cursor = "CURSOR",
+    Fetch = "FETCH",
+    Open = "OPEN",
+    Close = "CLOSE",
+    Offset = "OFFSET",
+    Except = "EXCEPT",
+    Intersect = "INTERSECT",
+    Window = "WINDOW",
+    Over = "OVER",
+    Partition = "PARTITION",
+    Range = "RANGE",
+    Rows = "ROWS",
+    Preceding = "PRECEDING",
+    Following = "FOLLOWING",
+    Current = "CURRENT",
+    Row = "ROW",
+    Natural = "NATURAL",
+    Cross = "CROSS",
+    Using = "USING",
+    Lateral = "LATERAL",
+    Recursive = "RECURSIVE",
+    Materialized = "MATERIALIZED",
+    Temporary = "TEMPORARY",
+}
+
+fn match_from_str(value: &str) -> Option<Keyword> {
+    match value {
+        "ADD" => Some(Keyword::Add),
+        "ALL" => Some(Keyword::All),
+        "ALTER" => Some(Keyword::Alter),
+        "AND" => Some(Keyword::And),
+        "ANY" => Some(Keyword::Any),
+        "AS" => Some(Keyword::As),
+        "ASC" => Some(Keyword::Asc),
+        "BACKUP" => Some(Keyword::Backup),
+        "BETWEEN" => Some(Keyword::Between),
+        "BY" => Some(Keyword::By),
+        "CASE" => Some(Keyword::Case),
+        "CHECK" => Some(Keyword::Check),
+        "COLUMN" => Some(Keyword::Column),
+        "CONSTRAINT" => Some(Keyword::Constraint),
+        "CREATE" => Some(Keyword::Create),
+        "DATABASE" => Some(Keyword::Database),
+        "DEFAULT" => Some(Keyword::Default),
+        "DELETE" => Some(Keyword::Delete),
+        "DESC" => Some(Keyword::Desc),
+        "DISTINCT" => Some(Keyword::Distinct),
+        "DROP" => Some(Keyword::Drop),
+        "EXEC" => Some(Keyword::Exec),
+        "EXISTS" => Some(Keyword::Exists),
+        "FOREIGN" => Some(Keyword::Foreign),
+        "FROM" => Some(Keyword::From),
+        "FULL" => Some(Keyword::Full),
+        "GROUP" => Some(Keyword::Group),
+        "HAVING" => Some(Keyword::Having),
+        "IN" => Some(Keyword::In),
+        "INDEX" => Some(Keyword::Index),
+        "INNER" => Some(Keyword::Inner),
+        "INSERT" => Some(Keyword::Insert),
+        "INTO" => Some(Keyword::Into),
+        "IS" => Some(Keyword::Is),
+        "JOIN" => Some(Keyword::Join),
+        "KEY" => Some(Keyword::Key),
+        "LEFT" => Some(Keyword::Left),
+        "LIKE" => Some(Keyword::Like),
+        "LIMIT" => Some(Keyword::Limit),
+        "NOT" => Some(Keyword::Not),
+        "NULL" => Some(Keyword::Null),
+        "OR" => Some(Keyword::Or),
+        "ORDER" => Some(Keyword::Order),
+        "OUTER" => Some(Keyword::Outer),
+        "PRIMARY" => Some(Keyword::Primary),
+        "PROCEDURE" => Some(Keyword::Procedure),
+        "REPLACE" => Some(Keyword::Replace),
+        "RIGHT" => Some(Keyword::Right),
+        "ROWNUM" => Some(Keyword::Rownum),
+        "SELECT" => Some(Keyword::Select),
+        "SET" => Some(Keyword::Set),
+        "TABLE" => Some(Keyword::Table),
+        "TOP" => Some(Keyword::Top),
+        "TRUNCATE" => Some(Keyword::Truncate),
+        "UNION" => Some(Keyword::Union),
+        "UNIQUE" => Some(Keyword::Unique),
+        "UPDATE" => Some(Keyword::Update),
+        "VALUES" => Some(Keyword::Values),
+        "VIEW" => Some(Keyword::View),
+        "WHERE" => Some(Keyword::Where),
+        "WITH" => Some(Keyword::With),
+        "BEGIN" => Some(Keyword::Begin),
+        "COMMIT" => Some(Keyword::Commit),
+        "ROLLBACK" => Some(Keyword::Rollback),
+        "SAVEPOINT" => Some(Keyword::Savepoint),
+        "TRANSACTION" => Some(Keyword::Transaction),
+        "GRANT" => Some(Keyword::Grant),
+        "REVOKE" => Some(Keyword::Revoke),
+        "CASCADE" => Some(Keyword::Cascade),
+        "RESTRICT" => Some(Keyword::Restrict),
+        "REFERENCES" => Some(Keyword::References),
+        "TRIGGER" => Some(Keyword::Trigger),
+        "FUNCTION" => Some(Keyword::Function),
+        "RETURNS" => Some(Keyword::Returns),
+        "RETURN" => Some(Keyword::Return),
+        "DECLARE" => Some(Keyword::Declare),
+        "CURSOR" => Some(Keyword::Cursor),
+        "FETCH" => Some(Keyword::Fetch),
+        "OPEN" => Some(Keyword::Open),
+        "CLOSE" => Some(Keyword::Close),
+        "OFFSET" => Some(Keyword::Offset),
+        "EXCEPT" => Some(Keyword::Except),
+        "INTERSECT" => Some(Keyword::Intersect),
+        "WINDOW" => Some(Keyword::Window),
+        "OVER" => Some(Keyword::Over),
+        "PARTITION" => Some(Keyword::Partition),
+        "RANGE" => Some(Keyword::Range),
+        "ROWS" => Some(Keyword::Rows),
+        "PRECEDING" => Some(Keyword::Preceding),
+        "FOLLOWING" => Some(Keyword::Following),
+        "CURRENT" => Some(Keyword::Current),
+        "ROW" => Some(Keyword::Row),
+        "NATURAL" => Some(Keyword::Natural),
+        "CROSS" => Some(Keyword::Cross),
+        "USING" => Some(Keyword::Using),
+        "LATERAL" => Some(Keyword::Lateral),
+        "RECURSIVE" => Some(Keyword::Recursive),
+        "MATERIALIZED" => Some(Keyword::Materialized),
+        "TEMPORARY" => Some(Keyword::Temporary),
+        _ => None,
+    }
+}
+
+fn bench(name: &str, inputs: &[&str], from_str: impl Fn(&str) -> Option<Keyword>) {
+    let start = Instant::now();
+    let mut found = 0usize;
+    for _ in 0..ROUNDS {
+        for input in inputs {
+            found += from_str(black_box(input)).is_some() as usize;
+        }
+    }
+    let elapsed = start.elapsed();
+    let per_call = elapsed.as_secs_f64() * 1e9 / (ROUNDS * inputs.len()) as f64;
+    println!("{name:>9}: {elapsed:?} ({per_call:.2} ns/call, found {found})");
+}
+
+fn main() {
+    // Every keyword along with as many identifiers that are not keywords
+    let identifiers: Vec<String> = Keyword::iter()
+        .map(|keyword| format!("{}_ID", keyword.to_str()))
+        .collect();
+    let inputs: Vec<&str> = Keyword::iter()
+        .map(|keyword| keyword.to_str())
+        .chain(identifiers.iter().map(String::as_str))
+        .collect();
+
+    bench("generated", &inputs, Keyword::from_str);
+    bench("match", &inputs, match_from_str);
+}
@@ -1,10 +1,12 @@
 use proc_macro::TokenStream;
-use proc_macro2::TokenStream as TokenStream2;
+use proc_macro2::{TokenStream as TokenStream2, TokenTree};
 
 use syn::{parse_macro_input, Data, DeriveInput, Expr, Lit};
 
 use indiscriminant_lib::indiscriminant_bits;
 use indiscriminant_lib::indiscriminant_byte_str;
+use indiscriminant_lib::indiscriminant_code;
+use indiscriminant_lib::indiscriminant_flags;
 use indiscriminant_lib::indiscriminant_str;
 
 #[proc_macro_attribute]
@@ -31,6 +33,22 @@ pub fn indiscriminant_bits(args: TokenStream, input: TokenStream) -> TokenStream
     ))
 }
 
+#[proc_macro_attribute]
+pub fn indiscriminant_code(args: TokenStream, input: TokenStream) -> TokenStream {
+    TokenStream::from(indiscriminant_code::indiscriminant_code(
+        TokenStream2::from(args),
+        TokenStream2::from(input),
+    ))
+}
+
+#[proc_macro_attribute]
+pub fn indiscriminant_flags(args: TokenStream, input: TokenStream) -> TokenStream {
+    TokenStream::from(indiscriminant_flags::indiscriminant_flags(
+        TokenStream2::from(args),
+        TokenStream2::from(input),
+    ))
+}
+
 #[proc_macro_attribute]
 pub fn indiscriminant(args: TokenStream, input: TokenStream) -> TokenStream {
     let input_cloned = input.clone();
@@ -40,22 +58,30 @@ pub fn indiscriminant(args: TokenStream, input: TokenStream) -> TokenStream {
         Data::Enum(data) => data,
         _ => panic!("Attribute not applied to enum!"),
     };
-    assert!(data.variants.len() > 0, "Enum is empty of any variants!");
+    assert!(!data.variants.is_empty(), "Enum is empty of any variants!");
 
     let v = data.variants.iter().next().unwrap();
 
-    let literal = match &v.discriminant {
-        Some((_, Expr::Lit(literal))) => literal,
-        _ => panic!("Literal not found for first discriminant!"),
-    };
-
     let args = TokenStream2::from(args);
     let input = TokenStream2::from(input);
-    let result = match &literal.lit {
-        Lit::Str(_) => indiscriminant_str::indiscriminant_str(args, input),
-        Lit::ByteStr(_) => indiscriminant_byte_str::indiscriminant_byte_str(args, input),
-        Lit::Byte(_) | Lit::Int(_) => indiscriminant_bits::indiscriminant_bits(args, input),
-        _ => panic!("First literal is not string, byte-string, or integer!"),
+
+    // Only string enums can leave out discriminants, relying on rename_all or
+    // glob patterns
+    let renamed = args
+        .clone()
+        .into_iter()
+        .any(|token| matches!(token, TokenTree::Ident(ident) if ident == "rename_all"))
+        || v.attrs.iter().any(|attr| attr.path.is_ident("glob"));
+
+    let result = match &v.discriminant {
+        Some((_, Expr::Lit(literal))) => match &literal.lit {
+            Lit::Str(_) => indiscriminant_str::indiscriminant_str(args, input),
+            Lit::ByteStr(_) => indiscriminant_byte_str::indiscriminant_byte_str(args, input),
+            Lit::Byte(_) | Lit::Int(_) => indiscriminant_bits::indiscriminant_bits(args, input),
+            _ => panic!("First literal is not string, byte-string, or integer!"),
+        },
+        None if renamed => indiscriminant_str::indiscriminant_str(args, input),
+        _ => panic!("Literal not found for first discriminant!"),
     };
     TokenStream::from(result)
 }
@@ -0,0 +1,65 @@
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::*;
+
+use syn::LitByteStr;
+
+// Below this many discriminants a plain chain of comparisons is as fast
+const DISPATCH_MIN_VARIANTS: usize = 8;
+
+/// Generates statements returning the result for the discriminant `bytes` is
+/// equal to, falling through when there is none. Larger sets first dispatch on
+/// the length and the first byte so only a few discriminants are compared,
+/// all in a form usable in const fn
+pub(crate) fn generate_exact_match(
+    bytes: &Ident,
+    entries: &[(Vec<u8>, TokenStream)],
+) -> TokenStream {
+    if entries.len() < DISPATCH_MIN_VARIANTS {
+        return generate_comparisons(bytes, entries);
+    }
+
+    // Group by length then first byte, keeping declaration order in a group
+    let mut entries: Vec<&(Vec<u8>, TokenStream)> = entries.iter().collect();
+    entries.sort_by_key(|(discriminant, _)| (discriminant.len(), discriminant.first().copied()));
+    let mut length_arms = quote!();
+    for length_group in entries.chunk_by(|a, b| a.0.len() == b.0.len()) {
+        let length = length_group[0].0.len();
+        if length == 0 {
+            let result = &length_group[0].1;
+            length_arms.extend(quote! { 0 => return #result, });
+            continue;
+        }
+        let mut byte_arms = quote!();
+        for byte_group in length_group.chunk_by(|a, b| a.0[0] == b.0[0]) {
+            let byte = byte_group[0].0[0];
+            let byte_group: Vec<_> = byte_group.iter().map(|entry| (*entry).clone()).collect();
+            let comparisons = generate_comparisons(bytes, &byte_group);
+            byte_arms.extend(quote! { #byte => { #comparisons } });
+        }
+        length_arms.extend(quote! {
+            #length => match #bytes[0] {
+                #byte_arms
+                _ => {}
+            },
+        });
+    }
+    quote! {
+        match #bytes.len() {
+            #length_arms
+            _ => {}
+        }
+    }
+}
+
+fn generate_comparisons(bytes: &Ident, entries: &[(Vec<u8>, TokenStream)]) -> TokenStream {
+    let mut comparisons = quote!();
+    for (discriminant, result) in entries {
+        let discriminant = LitByteStr::new(discriminant, Span::call_site());
+        comparisons.extend(quote! {
+            if ::indiscriminant::__private::bytes_eq(#bytes, #discriminant) {
+                return #result;
+            }
+        });
+    }
+    comparisons
+}
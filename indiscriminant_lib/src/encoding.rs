@@ -0,0 +1,49 @@
+/// Bit patterns an integer enum's ordinal discriminants are encoded as by
+/// `to_int` and `from_int`
+#[derive(PartialEq, Clone, Copy)]
+pub(crate) enum Encoding {
+    Binary,
+    Gray,
+    OneHot,
+    Thermometer,
+    Bcd,
+}
+
+impl Encoding {
+    pub(crate) fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "binary" => Some(Self::Binary),
+            "gray" => Some(Self::Gray),
+            "one_hot" => Some(Self::OneHot),
+            "thermometer" => Some(Self::Thermometer),
+            "bcd" => Some(Self::Bcd),
+            _ => None,
+        }
+    }
+
+    /// Encodes an ordinal, or returns `None` if the pattern needs more than
+    /// `usize` bits
+    pub(crate) fn encode(&self, ordinal: usize) -> Option<usize> {
+        match self {
+            Self::Binary => Some(ordinal),
+            Self::Gray => Some(ordinal ^ (ordinal >> 1)),
+            Self::OneHot => 1usize.checked_shl(ordinal as u32),
+            Self::Thermometer => match 1usize.checked_shl(ordinal as u32) {
+                Some(bit) => Some(bit - 1),
+                None if ordinal == usize::BITS as usize => Some(usize::MAX),
+                None => None,
+            },
+            Self::Bcd => {
+                let (mut ordinal, mut pattern, mut shift) = (ordinal, 0usize, 0u32);
+                while ordinal > 0 {
+                    pattern |= (ordinal % 10)
+                        .checked_shl(shift)
+                        .filter(|_| shift < usize::BITS)?;
+                    ordinal /= 10;
+                    shift += 4;
+                }
+                Some(pattern)
+            }
+        }
+    }
+}
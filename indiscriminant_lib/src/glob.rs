@@ -0,0 +1,101 @@
+use proc_macro2::{Ident, TokenStream};
+use quote::*;
+
+use syn::{Attribute, LitStr};
+
+/// A wildcard pattern from a `#[glob("..")]` variant attribute, where `*`
+/// matches any sequence of characters and `?` matches any one character
+pub(crate) struct Glob {
+    pattern: String,
+    variant: Ident,
+}
+
+impl Glob {
+    pub(crate) fn parse(variant: &Ident, attrs: &[Attribute]) -> Vec<Self> {
+        attrs
+            .iter()
+            .filter(|attr| attr.path.is_ident("glob"))
+            .map(|attr| match attr.parse_args::<LitStr>() {
+                Ok(pattern) => Self {
+                    pattern: pattern.value(),
+                    variant: variant.clone(),
+                },
+                Err(_) => panic!("Glob pattern not a string for variant {}!", variant),
+            })
+            .collect()
+    }
+
+    /// Picks the representative string for a variant with no discriminant by
+    /// dropping the `*` wildcards from its first pattern
+    pub(crate) fn canonical(globs: &[Self]) -> Option<String> {
+        Some(globs.first()?.pattern.replace('*', ""))
+    }
+
+    /// Generates a compile-time check that the discriminant matches one of
+    /// the variant's patterns, using the same matcher as the generated code
+    pub(crate) fn generate_check(globs: &[Self], discriminant: &str, message: &str) -> TokenStream {
+        if globs.is_empty() {
+            return quote!();
+        }
+        let patterns = globs.iter().map(|glob| &glob.pattern);
+        quote! {
+            const _: () = assert!(
+                #(::indiscriminant::__private::glob_match(#patterns, #discriminant))||*,
+                #message
+            );
+        }
+    }
+
+    fn literal_len(&self) -> usize {
+        self.pattern.chars().filter(|c| *c != '*').count()
+    }
+}
+
+/// Generates checks that return the first matching pattern's variant, trying
+/// longer patterns (not counting `*`) first and then declaration order
+pub(crate) fn generate_glob_match(name: &Ident, globs: &[Glob], has_default: bool) -> TokenStream {
+    let mut globs: Vec<&Glob> = globs.iter().collect();
+    globs.sort_by_key(|glob| std::cmp::Reverse(glob.literal_len()));
+    let mut checks = quote!();
+    for glob in globs {
+        let (pattern, variant) = (&glob.pattern, &glob.variant);
+        let result = if has_default {
+            quote! { #name::#variant }
+        } else {
+            quote! { Some(#name::#variant) }
+        };
+        checks.extend(quote! {
+            if ::indiscriminant::__private::glob_match(#pattern, value) {
+                return #result;
+            }
+        });
+    }
+    checks
+}
+
+/// Generates a search for the longest prefix of `value` that a pattern matches,
+/// leaving it in `result` if longer than the discriminant already found there
+pub(crate) fn generate_glob_prefix(name: &Ident, globs: &[Glob]) -> TokenStream {
+    if globs.is_empty() {
+        return quote!();
+    }
+    let checks = generate_glob_match(name, globs, false);
+    quote! {
+        fn glob(value: &str) -> Option<#name> {
+            #checks
+            None
+        }
+        let shortest = result.as_ref().map_or(0, |(_, length)| length + 1);
+        let mut end = value.len();
+        while end >= shortest {
+            if let Some(variant) = glob(&value[..end]) {
+                result = Some((variant, end));
+                break;
+            }
+            match value[..end].char_indices().next_back() {
+                Some((start, _)) => end = start,
+                None => break,
+            }
+        }
+    }
+}
@@ -1,171 +1,604 @@
+use std::collections::HashSet;
+
 use proc_macro2::{TokenStream, TokenTree};
 use quote::*;
-use std::collections::HashMap;
 
-use syn::{parse2, Attribute, Data, DeriveInput, Visibility};
+use syn::{parse2, Attribute, Data, DataEnum, DeriveInput, Fields, Generics, Ident, Visibility};
+
+use crate::encoding::Encoding;
+use crate::iteration::generate_iteration;
+use crate::raw::generate_raw_constants;
+use crate::{get_vis, split_args, IntegerType};
 
-use crate::{get_vis, IntegerType};
+struct Args {
+    integer_type: IntegerType,
+    bit_width: u8,
+    default: Option<Option<usize>>,
+    encoding: Encoding,
+}
 
-fn parse_args(args: TokenStream) -> (IntegerType, u8, Option<Option<usize>>) {
+fn parse_args(args: TokenStream) -> Args {
+    let mut args = split_args(args).into_iter();
     // Parse integer type
-    let mut iter = args.into_iter();
-    let integer_type = match iter.next() {
-        Some(TokenTree::Ident(ident)) => {
+    let integer_type = match args.next().as_deref() {
+        Some([TokenTree::Ident(ident)]) => {
             IntegerType::from_str(&ident.to_string()).expect("Invalid integer type argument!")
         }
         _ => panic!("Invalid arguments!"),
     };
-    // Check for comma indicating more arguments
-    match iter.next() {
-        Some(TokenTree::Punct(punct)) if punct.to_string() == "," => {}
-        Some(_) => panic!("Macro expected comma!"),
-        None => return (integer_type.clone(), integer_type.get_width(), None),
-    }
-    // Check for explicit bit-width
-    let (bit_width, next) = match iter.next() {
-        Some(TokenTree::Literal(literal)) => {
-            let bit_width = literal
-                .to_string()
-                .parse::<u8>()
-                .expect("Invalid bit-width!");
-            assert!(
-                bit_width <= integer_type.get_width(),
-                "Bit-width {} too large for integer type {}!",
-                bit_width,
-                integer_type.to_str()
-            );
-            // Check for comma indicating more arguments
-            match iter.next() {
-                Some(TokenTree::Punct(punct)) if punct.to_string() == "," => {
-                    (bit_width, iter.next())
-                }
-                Some(_) => panic!("Macro expected comma!"),
-                None => return (integer_type, bit_width, None),
-            }
-        }
-        next => (integer_type.get_width(), next),
+    let mut result = Args {
+        bit_width: integer_type.get_width(),
+        integer_type,
+        default: None,
+        encoding: Encoding::Binary,
     };
-    // Check for explicit default value
-    match (next, iter.next(), iter.next(), iter.next()) {
-        (
-            Some(TokenTree::Ident(ident)),
-            Some(TokenTree::Punct(punct)),
-            Some(TokenTree::Literal(literal)),
-            None,
-        ) => {
-            assert!(ident.to_string() == "Default", "Invalid arguments!");
-            assert!(punct.to_string() == "=", "Invalid arguments!");
-            let default_value = literal
-                .to_string()
-                .parse::<usize>()
-                .expect("Invalid default discriminant value!");
-            (integer_type, bit_width, Some(Some(default_value)))
-        }
-        (Some(TokenTree::Ident(ident)), None, None, None) => {
-            assert!(ident.to_string() == "Default", "Invalid arguments!");
-            (integer_type, bit_width, Some(None))
+    // Parse the optional bit-width, default value and encoding, each at most once
+    let mut encoding_given = false;
+    for (i, arg) in args.enumerate() {
+        match &arg[..] {
+            [TokenTree::Literal(literal)] if i == 0 => {
+                let bit_width = literal
+                    .to_string()
+                    .parse::<u8>()
+                    .expect("Invalid bit-width!");
+                assert!(
+                    bit_width <= result.integer_type.get_width(),
+                    "Bit-width {} too large for integer type {}!",
+                    bit_width,
+                    result.integer_type.to_str()
+                );
+                result.bit_width = bit_width;
+            }
+            [TokenTree::Ident(ident), ..] if ident == "Default" && result.default.is_some() => {
+                panic!("Default given more than once!")
+            }
+            [TokenTree::Ident(ident), ..] if ident == "encoding" && encoding_given => {
+                panic!("Encoding given more than once!")
+            }
+            [TokenTree::Ident(ident)] if ident == "Default" => result.default = Some(None),
+            [TokenTree::Ident(ident), TokenTree::Punct(punct), TokenTree::Literal(literal)]
+                if ident == "Default" && punct.as_char() == '=' =>
+            {
+                let default_value = literal
+                    .to_string()
+                    .parse::<usize>()
+                    .expect("Invalid default discriminant value!");
+                result.default = Some(Some(default_value));
+            }
+            [TokenTree::Ident(ident), TokenTree::Punct(punct), TokenTree::Ident(encoding)]
+                if ident == "encoding" && punct.as_char() == '=' =>
+            {
+                result.encoding = Encoding::from_str(&encoding.to_string())
+                    .unwrap_or_else(|| panic!("Unknown encoding {}!", encoding));
+                encoding_given = true;
+            }
+            _ => panic!("Invalid arguments!"),
         }
-        (None, None, None, None) => (integer_type, bit_width, None),
-        _ => panic!("Invalid arguments!",),
     }
+    result
 }
 
 fn generate_code_default(
     name: String,
-    integer_type: IntegerType,
-    bit_width: u8,
-    attrs: &Vec<Attribute>,
+    args: &Args,
+    attrs: &[Attribute],
     vis: &Visibility,
-    variants: HashMap<String, usize>,
+    variants: Vec<(String, usize)>,
     default_variant: Option<usize>,
 ) -> TokenStream {
     let name = format_ident!("{}", name);
+    let (integer_type, bit_width, encoding) = (&args.integer_type, args.bit_width, args.encoding);
     let itype = format_ident!("{}", integer_type.to_str());
 
     // Implement functions to convert generated enum to/from integers
     let mut variants_quote = quote!();
-    let mut to_matches = quote!();
     let mut from_matches = quote!();
+    let mut values = Vec::new();
     for (variant_name, discriminant) in &variants {
         let variant_name = format_ident!("{}", variant_name);
+        values.push((variant_name.clone(), *discriminant));
         variants_quote.extend(quote! { #variant_name = #discriminant as #itype, });
-        to_matches.extend(quote! { #name::#variant_name => #discriminant as #itype, });
-        let discriminant = integer_type.quote_discriminant(*discriminant);
-        from_matches.extend(quote! { #discriminant => #name::#variant_name, });
+        let encoded = integer_type.quote_discriminant(encoding.encode(*discriminant).unwrap());
+        from_matches.extend(quote! { #encoded => #name::#variant_name, });
     }
     // Handle an explicit default variant
     if let Some(default_variant) = default_variant {
+        values.push((format_ident!("Default"), default_variant));
         variants_quote.extend(quote! { Default = #default_variant as #itype, });
-        to_matches.extend(quote! { #name::Default => #default_variant as #itype, });
         from_matches.extend(quote! { _ => #name::Default, });
     // Handle not having a default but also not fully covering the native
     // integer space according to the compiler
-    } else if variants.len() < (1usize << integer_type.get_width()) {
-        let variant_name = format_ident!("{}", variants.iter().next().unwrap().0);
+    } else if 1usize
+        .checked_shl(integer_type.get_width() as u32)
+        .map_or(true, |space| variants.len() < space)
+    {
+        let variant_name = format_ident!("{}", variants.first().unwrap().0);
         from_matches.extend(quote! { _ => #name::#variant_name, });
     }
+    let lookup_table = match encoding {
+        Encoding::Binary => generate_lookup_table(bit_width, &values),
+        _ => None,
+    };
+    let from_body = match lookup_table {
+        Some(lookup_table) => {
+            let fallback = match default_variant {
+                Some(_) => format_ident!("Default"),
+                None => format_ident!("{}", variants.first().unwrap().0),
+            };
+            quote! {
+                #lookup_table
+                if VALID[(masked_value >> 3) as usize] & (1 << (masked_value & 7)) != 0 {
+                    // SAFETY: The bitmap only marks values that are discriminants
+                    unsafe { ::core::mem::transmute::<#itype, Self>(masked_value) }
+                } else {
+                    #name::#fallback
+                }
+            }
+        }
+        None => quote! {
+            match masked_value {
+                #from_matches
+            }
+        },
+    };
 
     // Construct resulting struct and impl functions
     let vis = get_vis(vis);
-    let bit_mask: usize = (1 << bit_width) - 1;
+    let bit_mask = bit_mask(bit_width);
     let attrs = attrs.iter().map(|attr| quote! { #attr });
-    TokenStream::from(quote! {
+    let to_body = generate_to_int(&name, integer_type, encoding, &values);
+    let values = generate_code_values(&name, integer_type, bit_width, encoding, &vis, values);
+    let bytes = generate_code_bytes(&name, integer_type, &vis, quote! { Self }, quote! { const });
+    quote! {
         #(#attrs)*
         #[repr(#itype)]
         #vis enum #name {
             #variants_quote
         }
         impl #name {
-            #vis fn to_int(&self) -> #itype {
-                match self {
-                    #to_matches
-                }
+            #vis const fn to_int(&self) -> #itype {
+                #to_body
             }
-            #vis fn from_int(value: #itype) -> Self {
+            #vis const fn from_int(value: #itype) -> Self {
                 let masked_value = #bit_mask as #itype & value;
-                match masked_value {
-                    #from_matches
-                }
+                #from_body
             }
         }
-    })
+        impl ::indiscriminant::Bits for #name {
+            type Int = #itype;
+            const BIT_WIDTH: u8 = #bit_width;
+            fn to_int(&self) -> #itype {
+                #name::to_int(self)
+            }
+            fn try_from_int(value: #itype) -> Option<Self> {
+                Some(#name::from_int(value))
+            }
+        }
+        #values
+        #bytes
+    }
 }
 
 fn generate_code(
     name: String,
-    integer_type: IntegerType,
-    bit_width: u8,
-    attrs: &Vec<Attribute>,
+    args: &Args,
+    attrs: &[Attribute],
     vis: &Visibility,
-    variants: HashMap<String, usize>,
+    variants: Vec<(String, usize)>,
 ) -> TokenStream {
     let name = format_ident!("{}", name);
+    let (integer_type, bit_width, encoding) = (&args.integer_type, args.bit_width, args.encoding);
     let itype = format_ident!("{}", integer_type.to_str());
 
     // Implement functions to convert generated enum to/from integers
     let mut variants_quote = quote!();
-    let mut to_matches = quote!();
     let mut from_matches = quote!();
+    let mut values = Vec::new();
     for (variant_name, discriminant) in variants {
         let variant_name = format_ident!("{}", variant_name);
+        values.push((variant_name.clone(), discriminant));
         variants_quote.extend(quote! { #variant_name = #discriminant as #itype, });
-        to_matches.extend(quote! { #name::#variant_name => #discriminant as #itype, });
-        let discriminant = integer_type.quote_discriminant(discriminant);
-        from_matches.extend(quote! { #discriminant => Some(#name::#variant_name), });
+        let encoded = integer_type.quote_discriminant(encoding.encode(discriminant).unwrap());
+        from_matches.extend(quote! { #encoded => Some(#name::#variant_name), });
     }
     from_matches.extend(quote! { _ => None, });
+    let lookup_table = match encoding {
+        Encoding::Binary => generate_lookup_table(bit_width, &values),
+        _ => None,
+    };
+    let from_body = match lookup_table {
+        Some(lookup_table) => quote! {
+            #lookup_table
+            if VALID[(masked_value >> 3) as usize] & (1 << (masked_value & 7)) != 0 {
+                // SAFETY: The bitmap only marks values that are discriminants
+                Some(unsafe { ::core::mem::transmute::<#itype, Self>(masked_value) })
+            } else {
+                None
+            }
+        },
+        None => quote! {
+            match masked_value {
+                #from_matches
+            }
+        },
+    };
 
     // Construct resulting struct and impl functions
     let vis = get_vis(vis);
-    let bit_mask: usize = (1 << bit_width) - 1;
+    let bit_mask = bit_mask(bit_width);
     let attrs = attrs.iter().map(|attr| quote! { #attr });
-    TokenStream::from(quote! {
+    let to_body = generate_to_int(&name, integer_type, encoding, &values);
+    let values = generate_code_values(&name, integer_type, bit_width, encoding, &vis, values);
+    let bytes = generate_code_bytes(
+        &name,
+        integer_type,
+        &vis,
+        quote! { Option<Self> },
+        quote! { const },
+    );
+    quote! {
         #(#attrs)*
         #[repr(#itype)]
         #vis enum #name {
             #variants_quote
         }
         impl #name {
+            #vis const fn to_int(&self) -> #itype {
+                #to_body
+            }
+            #vis const fn from_int(value: #itype) -> Option<Self> {
+                let masked_value = #bit_mask as #itype & value;
+                #from_body
+            }
+        }
+        impl ::indiscriminant::Bits for #name {
+            type Int = #itype;
+            const BIT_WIDTH: u8 = #bit_width;
+            fn to_int(&self) -> #itype {
+                #name::to_int(self)
+            }
+            fn try_from_int(value: #itype) -> Option<Self> {
+                #name::from_int(value)
+            }
+        }
+        #values
+        #bytes
+    }
+}
+
+// Lookup tables are only worth their size for wide enums filling at least a
+// quarter of a value space small enough to keep the table compact
+/// The mask of the low `bit_width` bits, which may be all 128
+fn bit_mask(bit_width: u8) -> u128 {
+    u128::MAX.checked_shr(128 - bit_width as u32).unwrap_or(0)
+}
+
+const TABLE_MIN_VARIANTS: usize = 16;
+const TABLE_MAX_BIT_WIDTH: u8 = 16;
+const TABLE_MIN_DENSITY: usize = 4;
+
+/// Generates a `VALID` bitmap marking each discriminant, or nothing when the
+/// enum is better decoded with a `match`
+fn generate_lookup_table(bit_width: u8, values: &[(Ident, usize)]) -> Option<TokenStream> {
+    let space = 1usize << bit_width.min(TABLE_MAX_BIT_WIDTH);
+    if values.len() < TABLE_MIN_VARIANTS
+        || bit_width > TABLE_MAX_BIT_WIDTH
+        || values.len() * TABLE_MIN_DENSITY < space
+    {
+        return None;
+    }
+    let mut bitmap = vec![0u8; space.div_ceil(8)];
+    for (_, discriminant) in values {
+        bitmap[discriminant >> 3] |= 1 << (discriminant & 7);
+    }
+    let length = bitmap.len();
+    Some(quote! {
+        const VALID: [u8; #length] = [#(#bitmap),*];
+    })
+}
+
+/// Generates the body of `to_int`, which reads the discriminant straight from
+/// the enum when it is not encoded
+fn generate_to_int(
+    name: &Ident,
+    integer_type: &IntegerType,
+    encoding: Encoding,
+    values: &[(Ident, usize)],
+) -> TokenStream {
+    // Casting each variant's path rather than *self needs no Copy bound
+    if encoding == Encoding::Binary {
+        let itype = format_ident!("{}", integer_type.to_str());
+        let variants = values.iter().map(|(variant, _)| variant);
+        return quote! {
+            match self {
+                #(#name::#variants => #name::#variants as #itype,)*
+            }
+        };
+    }
+    let (variants, encoded): (Vec<_>, Vec<_>) = values
+        .iter()
+        .map(|(variant, discriminant)| {
+            let encoded = encoding.encode(*discriminant).unwrap();
+            (variant, integer_type.quote_discriminant(encoded))
+        })
+        .unzip();
+    quote! {
+        match self {
+            #(#name::#variants => #encoded,)*
+        }
+    }
+}
+
+/// Generates conversions to and from the backing integer's bytes, built on
+/// `to_int` and `from_int`
+fn generate_code_bytes(
+    name: &Ident,
+    integer_type: &IntegerType,
+    vis: &TokenStream,
+    from_type: TokenStream,
+    constness: TokenStream,
+) -> TokenStream {
+    let itype = format_ident!("{}", integer_type.to_str());
+    let size = integer_type.get_width() as usize / 8;
+    quote! {
+        impl #name {
+            #vis #constness fn to_be_bytes(&self) -> [u8; #size] {
+                self.to_int().to_be_bytes()
+            }
+            #vis #constness fn to_le_bytes(&self) -> [u8; #size] {
+                self.to_int().to_le_bytes()
+            }
+            #vis #constness fn from_be_bytes(bytes: [u8; #size]) -> #from_type {
+                Self::from_int(#itype::from_be_bytes(bytes))
+            }
+            #vis #constness fn from_le_bytes(bytes: [u8; #size]) -> #from_type {
+                Self::from_int(#itype::from_le_bytes(bytes))
+            }
+        }
+    }
+}
+
+fn generate_code_values(
+    name: &Ident,
+    integer_type: &IntegerType,
+    bit_width: u8,
+    encoding: Encoding,
+    vis: &TokenStream,
+    mut values: Vec<(Ident, usize)>,
+) -> TokenStream {
+    let itype = format_ident!("{}", integer_type.to_str());
+    let raws: Vec<(Ident, TokenStream)> = values
+        .iter()
+        .map(|(variant, discriminant)| {
+            let encoded = encoding.encode(*discriminant).unwrap();
+            (variant.clone(), integer_type.quote_discriminant(encoded))
+        })
+        .collect();
+    let raw_constants = generate_raw_constants(name, vis, quote! { #itype }, &raws);
+
+    // Variants are listed and stepped through in discriminant order
+    values.sort_by_key(|(_, discriminant)| *discriminant);
+    let order: Vec<Ident> = values.iter().map(|(variant, _)| variant.clone()).collect();
+    let iteration = generate_iteration(name, vis, &order);
+    let (min, max) = (&order[0], &order[order.len() - 1]);
+    let encoded: Vec<TokenStream> = raws.iter().map(|(_, encoded)| encoded.clone()).collect();
+    let unchecked_body = if encoding == Encoding::Binary {
+        quote! {
+            // SAFETY: The caller guarantees the value is a discriminant of
+            // this #[repr] enum
+            unsafe { ::core::mem::transmute::<#itype, Self>(value) }
+        }
+    } else {
+        let variants = raws.iter().map(|(variant, _)| variant);
+        quote! {
+            match value {
+                #(#encoded => #name::#variants,)*
+                // SAFETY: The caller guarantees the value is one of the
+                // encoded discriminants
+                _ => unsafe { ::core::hint::unreachable_unchecked() },
+            }
+        }
+    };
+
+    let bit_mask = bit_mask(bit_width);
+    quote! {
+        #iteration
+        #raw_constants
+        impl #name {
+            #vis const MIN: Self = #name::#min;
+            #vis const MAX: Self = #name::#max;
+            #vis const VALID_MASK: #itype = #bit_mask as #itype;
+            #vis const fn is_valid(value: #itype) -> bool {
+                matches!(value, #(#encoded)|*)
+            }
+            /// Converts from an integer without checking it is a discriminant
+            ///
+            /// # Safety
+            ///
+            /// `value` must be exactly one of the discriminants, as checked by
+            /// `is_valid`
+            #vis const unsafe fn from_int_unchecked(value: #itype) -> Self {
+                debug_assert!(Self::is_valid(value), "Value is not a discriminant!");
+                #unchecked_body
+            }
+        }
+    }
+}
+
+fn generate_code_payload(
+    args: TokenStream,
+    name: &Ident,
+    attrs: &[Attribute],
+    vis: &Visibility,
+    generics: &Generics,
+    data: &DataEnum,
+) -> TokenStream {
+    let kind = format_ident!("{}Kind", name);
+    let integer_type = parse_args(args.clone()).integer_type;
+    let itype = format_ident!("{}", integer_type.to_str());
+
+    // The tags become a field-less companion enum with the usual conversions
+    let mut kind_variants = quote!();
+    let mut variants_quote = quote!();
+    let mut kind_matches = quote!();
+    for v in data.variants.iter() {
+        let (variant_attrs, ident, fields) = (&v.attrs, &v.ident, &v.fields);
+        let tag = match &v.discriminant {
+            Some((_, expr)) => expr,
+            None => panic!("Discriminant not found for variant {}!", ident),
+        };
+        kind_variants.extend(quote! { #ident = #tag, });
+        variants_quote.extend(quote! { #(#variant_attrs)* #ident #fields, });
+        let pattern = match fields {
+            Fields::Named(_) => quote! { #name::#ident { .. } },
+            Fields::Unnamed(_) => quote! { #name::#ident(..) },
+            Fields::Unit => quote! { #name::#ident },
+        };
+        kind_matches.extend(quote! { #pattern => #kind::#ident, });
+    }
+    let kind_enum = indiscriminant_bits(
+        args,
+        quote! {
+            #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+            #vis enum #kind {
+                #kind_variants
+            }
+        },
+    );
+
+    // Construct resulting enum, keeping its generics, and impl functions
+    let vis = get_vis(vis);
+    let attrs = attrs.iter().map(|attr| quote! { #attr });
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    quote! {
+        #kind_enum
+        #(#attrs)*
+        #vis enum #name #generics #where_clause {
+            #variants_quote
+        }
+        impl #impl_generics #name #ty_generics #where_clause {
+            #vis const fn kind(&self) -> #kind {
+                match self {
+                    #kind_matches
+                }
+            }
+            #vis const fn tag(&self) -> #itype {
+                self.kind().to_int()
+            }
+        }
+    }
+}
+
+fn generate_code_nested(
+    name: &Ident,
+    integer_type: IntegerType,
+    bit_width: u8,
+    attrs: &[Attribute],
+    vis: &Visibility,
+    data: &DataEnum,
+) -> TokenStream {
+    let itype = format_ident!("{}", integer_type.to_str());
+    let bits = quote! { ::indiscriminant::Bits };
+
+    // The tag takes every bit above the widest nested enum, whose width is
+    // only known once the nested enums have been expanded
+    let mut variants_quote = quote!();
+    let mut to_matches = quote!();
+    let mut from_matches = quote!();
+    let mut shift_quotes = quote!();
+    let mut tags = HashSet::new();
+    for v in data.variants.iter() {
+        let ident = &v.ident;
+        let tag = match &v.discriminant {
+            Some((_, expr)) => match integer_type.parse_expr(expr) {
+                Ok(tag) => tag,
+                Err(_) => panic!("Non-integer discriminant found!"),
+            },
+            None => panic!("Discriminant not found for variant {}!", ident),
+        };
+        assert!(tags.insert(tag), "Duplicate discriminants found!");
+
+        let shifted_tag = if tag == 0 {
+            quote! { 0 }
+        } else {
+            quote! { (#tag as #itype) << Self::NESTED_SHIFT }
+        };
+        let tag = integer_type.quote_discriminant(tag);
+        let nested = v.attrs.iter().any(|attr| attr.path.is_ident("nested"));
+        let variant_attrs = v.attrs.iter().filter(|attr| !attr.path.is_ident("nested"));
+        match (nested, &v.fields) {
+            (true, Fields::Unnamed(fields)) if fields.unnamed.len() == 1 => {
+                let ty = &fields.unnamed[0].ty;
+                variants_quote.extend(quote! { #(#variant_attrs)* #ident(#ty), });
+                to_matches.extend(quote! {
+                    #name::#ident(inner) => #shifted_tag | #bits::to_int(inner) as #itype,
+                });
+                // Bits between the nested enum and the tag must be clear
+                from_matches.extend(quote! {
+                    #tag if nested_value
+                        .checked_shr(<#ty as #bits>::BIT_WIDTH as u32)
+                        .unwrap_or(0) == 0 => <#ty as #bits>::try_from_int(
+                        nested_value as <#ty as #bits>::Int
+                    ).map(#name::#ident),
+                });
+                shift_quotes.extend(quote! {
+                    if <#ty as #bits>::BIT_WIDTH > shift {
+                        shift = <#ty as #bits>::BIT_WIDTH;
+                    }
+                });
+            }
+            (false, Fields::Unit) => {
+                variants_quote.extend(quote! { #(#variant_attrs)* #ident, });
+                to_matches.extend(quote! { #name::#ident => #shifted_tag, });
+                from_matches.extend(quote! { #tag if nested_value == 0 => Some(#name::#ident), });
+            }
+            _ => panic!("Nested variant {} must hold exactly one enum!", ident),
+        }
+    }
+    from_matches.extend(quote! { _ => None, });
+    let max_tag = *tags.iter().max().unwrap();
+    assert!(
+        usize::BITS - max_tag.leading_zeros() <= bit_width as u32,
+        "Discriminant too big!"
+    );
+    let width_error = format!("Nested enums do not fit in {} bits!", bit_width);
+    let tag_error = "Discriminants do not fit above the nested enums!";
+
+    // Construct resulting struct and impl functions
+    let vis = get_vis(vis);
+    let bit_mask = bit_mask(bit_width);
+    let attrs = attrs.iter().map(|attr| quote! { #attr });
+    let bytes = generate_code_bytes(
+        name,
+        &integer_type,
+        &vis,
+        quote! { Option<Self> },
+        quote! {},
+    );
+    quote! {
+        #(#attrs)*
+        #vis enum #name {
+            #variants_quote
+        }
+        #bytes
+        const _: () = assert!(#name::NESTED_SHIFT <= #bit_width, #width_error);
+        const _: () = assert!(
+            match (#max_tag as u128).checked_shr(#bit_width.saturating_sub(#name::NESTED_SHIFT) as u32) {
+                Some(high) => high == 0,
+                None => true,
+            },
+            #tag_error
+        );
+        impl #name {
+            const NESTED_SHIFT: u8 = {
+                let mut shift = 0;
+                #shift_quotes
+                shift
+            };
+            const NESTED_MASK: #itype = match (1 as #itype).checked_shl(Self::NESTED_SHIFT as u32) {
+                Some(bit) => bit - 1,
+                None => #itype::MAX,
+            };
             #vis fn to_int(&self) -> #itype {
                 match self {
                     #to_matches
@@ -173,35 +606,99 @@ fn generate_code(
             }
             #vis fn from_int(value: #itype) -> Option<Self> {
                 let masked_value = #bit_mask as #itype & value;
-                match masked_value {
+                let nested_value = masked_value & Self::NESTED_MASK;
+                match masked_value.checked_shr(Self::NESTED_SHIFT as u32).unwrap_or(0) {
                     #from_matches
                 }
             }
         }
-    })
+        impl #bits for #name {
+            type Int = #itype;
+            const BIT_WIDTH: u8 = #bit_width;
+            fn to_int(&self) -> #itype {
+                #name::to_int(self)
+            }
+            fn try_from_int(value: #itype) -> Option<Self> {
+                #name::from_int(value)
+            }
+        }
+    }
 }
 
 pub fn indiscriminant_bits(args: TokenStream, input: TokenStream) -> TokenStream {
-    // Parse argument list into integer type and bit-width
-    let (integer_type, bit_width, default_value) = parse_args(args);
+    // Parse argument list into integer type, bit-width, default and encoding
+    let parsed = parse_args(args.clone());
+    let (integer_type, bit_width, encoding) = (
+        parsed.integer_type.clone(),
+        parsed.bit_width,
+        parsed.encoding,
+    );
+    let default_value = parsed.default;
 
     // Parse enum body
     let input = match parse2::<DeriveInput>(input) {
         Ok(input) => input,
-        Err(err) => return TokenStream::from(err.to_compile_error()),
+        Err(err) => return err.to_compile_error(),
     };
     let data = match input.data {
         Data::Enum(data) => data,
         _ => panic!("Attribute not applied to enum!"),
     };
-    assert!(data.variants.len() > 0, "Enum is empty of any variants!");
+    assert!(!data.variants.is_empty(), "Enum is empty of any variants!");
+
+    // Variants holding other enums select them with their high bits
+    let nested = data
+        .variants
+        .iter()
+        .any(|v| v.attrs.iter().any(|attr| attr.path.is_ident("nested")));
+    if nested {
+        assert!(
+            default_value.is_none(),
+            "Default variant not supported with nested variants!"
+        );
+        assert!(
+            encoding == Encoding::Binary,
+            "Encodings not supported with nested variants!"
+        );
+        return generate_code_nested(
+            &input.ident,
+            integer_type,
+            bit_width,
+            &input.attrs,
+            &input.vis,
+            &data,
+        );
+    }
+
+    // Variants carrying data are tagged through a field-less companion enum
+    if data
+        .variants
+        .iter()
+        .any(|v| !matches!(v.fields, Fields::Unit))
+    {
+        return generate_code_payload(
+            args,
+            &input.ident,
+            &input.attrs,
+            &input.vis,
+            &input.generics,
+            &data,
+        );
+    }
 
-    let max_variant = 1 << bit_width;
-    let mut variants = HashMap::new();
-    let mut discriminants = Vec::new();
+    // Discriminants are ordinals whose encoded patterns must fit the bit-width
+    // None when the bit-width covers every usize, as for full-width u64 flags
+    let max_variant = 1usize.checked_shl(bit_width as u32);
+    let fits = |ordinal: usize| {
+        encoding
+            .encode(ordinal)
+            .is_some_and(|e| max_variant.map_or(true, |max| e < max))
+    };
+    let mut variants = Vec::new();
+    let mut discriminants = HashSet::new();
     let has_default = if let Some(default_value) = default_value {
         if let Some(default_value) = default_value {
-            discriminants.push(default_value);
+            discriminants.insert(default_value);
         }
         true
     } else {
@@ -214,32 +711,27 @@ pub fn indiscriminant_bits(args: TokenStream, input: TokenStream) -> TokenStream
             panic!("Default variant already provided as argument!");
         }
         let discriminant = match &v.discriminant {
-            Some((_, expr)) => match integer_type.from_expr(expr) {
+            Some((_, expr)) => match integer_type.parse_expr(expr) {
                 Ok(discriminant) => discriminant,
                 Err(_) => panic!("Non-integer discriminant found!"),
             },
             None => panic!("Discriminant not found for variant {}!", ident),
         };
-        assert!(discriminant < max_variant, "Discriminant too big!");
-        match discriminants.binary_search(&discriminant) {
-            Ok(_) => panic!("Duplicate discriminants found!"),
-            Err(pos) => discriminants.insert(pos, discriminant),
-        }
-        variants.insert(ident, discriminant);
+        assert!(fits(discriminant), "Discriminant too big!");
+        assert!(
+            discriminants.insert(discriminant),
+            "Duplicate discriminants found!"
+        );
+        variants.push((ident, discriminant));
     }
 
     let default_value = if let Some(default_value) = default_value {
         if let Some(default_value) = default_value {
+            assert!(fits(default_value), "Default discriminant too big!");
             Some(default_value)
         } else {
             // Determine what an unused value can be for the default
-            let mut unused = None;
-            for i in 0..max_variant {
-                if !discriminants.contains(&i) {
-                    unused = Some(i);
-                    break;
-                }
-            }
+            let unused = (0..=usize::MAX).find(|i| !discriminants.contains(i) && fits(*i));
             if let Some(unused) = unused {
                 Some(unused)
             } else {
@@ -250,11 +742,10 @@ pub fn indiscriminant_bits(args: TokenStream, input: TokenStream) -> TokenStream
         None
     };
 
-    if default_value.is_some() || discriminants.len() == max_variant {
+    if default_value.is_some() || Some(discriminants.len()) == max_variant {
         generate_code_default(
             input.ident.to_string(),
-            integer_type,
-            bit_width,
+            &parsed,
             &input.attrs,
             &input.vis,
             variants,
@@ -263,8 +754,7 @@ pub fn indiscriminant_bits(args: TokenStream, input: TokenStream) -> TokenStream
     } else {
         generate_code(
             input.ident.to_string(),
-            integer_type,
-            bit_width,
+            &parsed,
             &input.attrs,
             &input.vis,
             variants,
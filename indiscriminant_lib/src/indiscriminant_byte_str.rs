@@ -1,133 +1,275 @@
-use proc_macro2::{TokenStream, TokenTree};
+use std::collections::HashSet;
+
+use proc_macro2::{Literal, TokenStream, TokenTree};
 use quote::*;
-use std::collections::HashMap;
 
-use syn::{parse2, Attribute, Data, DeriveInput, Expr, Lit, LitByteStr, Visibility};
+use syn::{parse2, Attribute, Data, DeriveInput, Expr, Ident, Lit, LitByteStr, Visibility};
 
-use crate::get_vis;
+use crate::dispatch::generate_exact_match;
+use crate::iteration::generate_iteration;
+use crate::raw::generate_raw_constants;
+use crate::recognizer::generate_recognizer;
+use crate::sniff::{generate_sniff, Signature};
+use crate::trie::generate_prefix_match;
+use crate::{get_vis, split_args};
 
 type Span = quote::__private::Span;
 
-fn parse_args(args: TokenStream) -> Option<(Vec<u8>, Span)> {
-    // Parse argument list into default string if one is given
-    let mut iter = args.into_iter();
-    match (iter.next(), iter.next(), iter.next(), iter.next()) {
-        (
-            Some(TokenTree::Ident(ident)),
-            Some(TokenTree::Punct(punct)),
-            Some(TokenTree::Literal(literal)),
-            None,
-        ) => {
-            if ident.to_string() != "Default" {
-                panic!("First argument should be Default");
-            }
-            if punct.to_string() != "=" {
-                panic!("Second argument should be =");
+struct Args {
+    default: Option<(Vec<u8>, Span)>,
+    size: Option<usize>,
+}
+
+fn parse_args(args: TokenStream) -> Args {
+    // Parse argument list into default string and fixed size if given
+    let mut result = Args {
+        default: None,
+        size: None,
+    };
+    for arg in split_args(args) {
+        let (ident, literal) = match &arg[..] {
+            [TokenTree::Ident(ident), TokenTree::Punct(punct), TokenTree::Literal(literal)]
+                if punct.as_char() == '=' =>
+            {
+                (ident.to_string(), Lit::new(literal.clone()))
             }
-            let s = literal.to_string();
-            if s.len() >= 3 && s.starts_with("b\"") && s.ends_with("\"") {
-                let s = s[2..s.len() - 1].as_bytes().to_vec();
-                Some((s, Span::from(literal.span())))
-            } else {
-                panic!("Default discriminant not a string!");
+            _ => panic!("Invalid arguments!"),
+        };
+        match (ident.as_str(), literal) {
+            ("Default", Lit::ByteStr(s)) => result.default = Some((s.value(), s.span())),
+            ("Default", _) => panic!("Default discriminant not a string!"),
+            ("size", Lit::Int(i)) => {
+                result.size = Some(i.base10_parse::<usize>().expect("Invalid size!"))
             }
+            ("size", _) => panic!("Size not an integer!"),
+            _ => panic!("Invalid arguments!"),
         }
-        (None, None, None, None) => None,
-        _ => panic!("Invalid arguments!"),
     }
+    result
 }
 
 fn generate_code(
     name: String,
-    attrs: &Vec<Attribute>,
+    attrs: &[Attribute],
     vis: &Visibility,
-    variants: HashMap<String, (Vec<u8>, Span)>,
+    variants: Vec<(String, (Vec<u8>, Span))>,
+    signatures: Vec<Signature>,
 ) -> TokenStream {
     let name = format_ident!("{}", name);
 
-    // Implement functions to convert generated enum to/from Option<&'static [u8]>
+    // Implement const functions to convert generated enum to/from Option<&'static [u8]>,
+    // slices are compared byte by byte since matching on them is not const
     let mut variants_quote = quote!();
     let mut to_quotes = quote!();
-    let mut from_quotes = quote!();
+    let mut from_entries = Vec::new();
+    let mut prefix_entries = Vec::new();
     for (variant_name, (discriminant, span)) in variants {
         let discriminant = LitByteStr::new(&discriminant, span);
         let variant_name = format_ident!("{}", variant_name);
         variants_quote.extend(quote! { #variant_name, });
         to_quotes.extend(quote! { #name::#variant_name => #discriminant, });
-        from_quotes.extend(quote! { #discriminant => Some(#name::#variant_name), });
+        from_entries.push((discriminant.value(), quote! { Some(#name::#variant_name) }));
+        prefix_entries.push((discriminant.value(), quote! { #name::#variant_name }));
     }
-    from_quotes.extend(quote! { _ => None, });
+    let from_match = generate_exact_match(&format_ident!("value"), &from_entries);
+
+    let prefix_match = generate_prefix_match(&prefix_entries);
 
     // Construct resulting struct and impl functions
     let vis = get_vis(vis);
+    let sniff = generate_sniff(&name, &vis, signatures);
+    let recognizer = generate_recognizer(&name, &vis, prefix_entries);
     let attrs = attrs.iter().map(|attr| quote! { #attr });
-    TokenStream::from(quote! {
+    quote! {
         #(#attrs)*
         #vis enum #name {
             #variants_quote
         }
         impl #name {
-            #vis fn to_byte_str(&self) -> &'static [u8] {
+            #vis const fn to_byte_str(&self) -> &'static [u8] {
                 match self {
                     #to_quotes
                 }
             }
-            #vis fn from_byte_str(value: &[u8]) -> Option<Self> {
-                match value {
-                    #from_quotes
-                }
+            #vis const fn from_byte_str(value: &[u8]) -> Option<Self> {
+                #from_match
+                None
+            }
+            #vis fn parse_prefix(bytes: &[u8]) -> Option<(Self, &[u8])> {
+                let mut result: Option<(Self, usize)> = None;
+                #prefix_match
+                result.map(|(variant, length)| (variant, &bytes[length..]))
             }
+            #sniff
         }
-    })
+        #recognizer
+    }
 }
 
 fn generate_code_default(
     name: String,
-    attrs: &Vec<Attribute>,
+    attrs: &[Attribute],
     vis: &Visibility,
-    variants: HashMap<String, (Vec<u8>, Span)>,
+    variants: Vec<(String, (Vec<u8>, Span))>,
     default_variant: (Vec<u8>, Span),
+    signatures: Vec<Signature>,
 ) -> TokenStream {
     let name = format_ident!("{}", name);
 
-    // Implement functions to convert generated enum to/from &'static [u8]
+    // Implement const functions to convert generated enum to/from &'static [u8]
     let mut variants_quote = quote!();
     let mut to_quotes = quote!();
-    let mut from_quotes = quote!();
+    let mut from_entries = Vec::new();
+    let mut prefix_entries = Vec::new();
     for (variant_name, (discriminant, span)) in variants {
         let discriminant = LitByteStr::new(&discriminant, span);
         let variant_name = format_ident!("{}", variant_name);
         variants_quote.extend(quote! { #variant_name, });
         to_quotes.extend(quote! { #name::#variant_name => #discriminant, });
-        from_quotes.extend(quote! { #discriminant => #name::#variant_name, });
+        from_entries.push((discriminant.value(), quote! { #name::#variant_name }));
+        prefix_entries.push((discriminant.value(), quote! { #name::#variant_name }));
     }
     let (discriminant, span) = default_variant;
     let discriminant = LitByteStr::new(&discriminant, span);
     variants_quote.extend(quote! { Default, });
     to_quotes.extend(quote! { #name::Default => #discriminant, });
-    from_quotes.extend(quote! { _ => #name::Default, });
+    let from_match = generate_exact_match(&format_ident!("value"), &from_entries);
+
+    let prefix_match = generate_prefix_match(&prefix_entries);
 
     // Construct resulting struct and impl functions
     let vis = get_vis(vis);
+    let sniff = generate_sniff(&name, &vis, signatures);
+    let recognizer = generate_recognizer(&name, &vis, prefix_entries);
     let attrs = attrs.iter().map(|attr| quote! { #attr });
-    TokenStream::from(quote! {
+    quote! {
         #(#attrs)*
         #vis enum #name {
             #variants_quote
         }
         impl #name {
-            #vis fn to_byte_str(&self) -> &'static [u8] {
+            #vis const fn to_byte_str(&self) -> &'static [u8] {
+                match self {
+                    #to_quotes
+                }
+            }
+            #vis const fn from_byte_str(value: &[u8]) -> Self {
+                #from_match
+                #name::Default
+            }
+            #vis fn parse_prefix(bytes: &[u8]) -> Option<(Self, &[u8])> {
+                let mut result: Option<(Self, usize)> = None;
+                #prefix_match
+                result.map(|(variant, length)| (variant, &bytes[length..]))
+            }
+            #sniff
+        }
+        #recognizer
+    }
+}
+
+fn generate_code_fixed_size(
+    name: String,
+    vis: &Visibility,
+    size: usize,
+    variants: &[(String, (Vec<u8>, Span))],
+    default_variant: Option<&(Vec<u8>, Span)>,
+) -> TokenStream {
+    let name = format_ident!("{}", name);
+
+    // Sizes matching an integer type are matched as a single integer compare
+    let itype = match size {
+        1 => Some(format_ident!("u8")),
+        2 => Some(format_ident!("u16")),
+        4 => Some(format_ident!("u32")),
+        8 => Some(format_ident!("u64")),
+        16 => Some(format_ident!("u128")),
+        _ => None,
+    };
+
+    let mut to_quotes = quote!();
+    let mut from_quotes = quote!();
+    for (variant_name, (discriminant, span)) in variants {
+        let variant_name = format_ident!("{}", variant_name);
+        let literal = LitByteStr::new(discriminant, *span);
+        to_quotes.extend(quote! { #name::#variant_name => *#literal, });
+        let pattern = if itype.is_some() {
+            let value = discriminant
+                .iter()
+                .fold(0u128, |value, byte| (value << 8) | *byte as u128);
+            let value = match size {
+                1 => Literal::u8_suffixed(value as u8),
+                2 => Literal::u16_suffixed(value as u16),
+                4 => Literal::u32_suffixed(value as u32),
+                8 => Literal::u64_suffixed(value as u64),
+                _ => Literal::u128_suffixed(value),
+            };
+            quote! { #value }
+        } else {
+            quote! { #literal }
+        };
+        if default_variant.is_some() {
+            from_quotes.extend(quote! { #pattern => #name::#variant_name, });
+        } else {
+            from_quotes.extend(quote! { #pattern => Some(#name::#variant_name), });
+        }
+    }
+    let (to_default, from_default, from_type) = if let Some((discriminant, span)) = default_variant
+    {
+        let literal = LitByteStr::new(discriminant, *span);
+        (
+            quote! { #name::Default => *#literal, },
+            quote! { _ => #name::Default, },
+            quote! { Self },
+        )
+    } else {
+        (quote! {}, quote! { _ => None, }, quote! { Option<Self> })
+    };
+    let from_value = match &itype {
+        Some(itype) => quote! { #itype::from_be_bytes(value) },
+        None => quote! { &value },
+    };
+
+    // Integer packing is only possible when the size matches an integer type
+    let vis = get_vis(vis);
+    let packing = itype.map(|itype| {
+        let to_be = format_ident!("to_{}_be", itype);
+        let to_le = format_ident!("to_{}_le", itype);
+        let from_be = format_ident!("from_{}_be", itype);
+        let from_le = format_ident!("from_{}_le", itype);
+        quote! {
+            #vis fn #to_be(&self) -> #itype {
+                #itype::from_be_bytes(self.to_array())
+            }
+            #vis fn #to_le(&self) -> #itype {
+                #itype::from_le_bytes(self.to_array())
+            }
+            #vis fn #from_be(value: #itype) -> #from_type {
+                Self::from_array(value.to_be_bytes())
+            }
+            #vis fn #from_le(value: #itype) -> #from_type {
+                Self::from_array(value.to_le_bytes())
+            }
+        }
+    });
+
+    quote! {
+        impl #name {
+            #vis fn to_array(&self) -> [u8; #size] {
                 match self {
                     #to_quotes
+                    #to_default
                 }
             }
-            #vis fn from_byte_str(value: &[u8]) -> Self {
-                match value {
+            #vis fn from_array(value: [u8; #size]) -> #from_type {
+                match #from_value {
                     #from_quotes
+                    #from_default
                 }
             }
+            #packing
         }
-    })
+    }
 }
 
 pub fn indiscriminant_byte_str(args: TokenStream, input: TokenStream) -> TokenStream {
@@ -138,19 +280,20 @@ pub fn indiscriminant_byte_str(args: TokenStream, input: TokenStream) -> TokenSt
     // let input = parse_macro_input!(input as DeriveInput);
     let input = match parse2::<DeriveInput>(input) {
         Ok(input) => input,
-        Err(err) => return TokenStream::from(err.to_compile_error()),
+        Err(err) => return err.to_compile_error(),
     };
     let data = match input.data {
         Data::Enum(data) => data,
         _ => panic!("Attribute not applied to enum!"),
     };
-    assert!(data.variants.len() > 0, "Enum is empty of any variants!");
+    assert!(!data.variants.is_empty(), "Enum is empty of any variants!");
 
     // Parse enum variants and discriminants
-    let mut variants = HashMap::new();
-    let mut discriminants = Vec::new();
-    let has_default = if let Some((literal, _)) = &args {
-        discriminants.push(literal.clone());
+    let mut variants = Vec::new();
+    let mut discriminants = HashSet::new();
+    let mut signatures = Vec::new();
+    let has_default = if let Some((literal, _)) = &args.default {
+        discriminants.insert(literal.clone());
         true
     } else {
         false
@@ -170,22 +313,89 @@ pub fn indiscriminant_byte_str(args: TokenStream, input: TokenStream) -> TokenSt
             Lit::ByteStr(b) => (b.value(), b.span()),
             _ => panic!("Non-byte-string literal found!"),
         };
-        match discriminants.binary_search(&discriminant) {
-            Ok(_) => panic!("Duplicate discriminants found!"),
-            Err(pos) => discriminants.insert(pos, discriminant.clone()),
+        assert!(
+            discriminants.insert(discriminant.clone()),
+            "Duplicate discriminants found!"
+        );
+        signatures.push(Signature::parse(&v.ident, &v.attrs, &discriminant));
+        variants.push((ident.to_string(), (discriminant, span)));
+    }
+
+    // Fixed-size discriminants all fit one array or integer type
+    let fixed_size = args.size.map(|size| {
+        for (variant_name, (discriminant, _)) in &variants {
+            assert!(
+                discriminant.len() == size,
+                "Discriminant for variant {} is not {} bytes!",
+                variant_name,
+                size
+            );
         }
-        variants.insert(ident.to_string(), (discriminant, span));
+        if let Some((literal, _)) = &args.default {
+            assert!(
+                literal.len() == size,
+                "Default discriminant is not {} bytes!",
+                size
+            );
+        }
+        generate_code_fixed_size(
+            input.ident.to_string(),
+            &input.vis,
+            size,
+            &variants,
+            args.default.as_ref(),
+        )
+    });
+
+    // Variants are listed and stepped through in declaration order
+    let mut order: Vec<Ident> = variants
+        .iter()
+        .map(|(variant_name, _)| format_ident!("{}", variant_name))
+        .collect();
+    if args.default.is_some() {
+        order.push(format_ident!("Default"));
     }
+    let iteration = generate_iteration(&input.ident, &get_vis(&input.vis), &order);
 
-    if let Some((literal, span)) = args {
+    let raws: Vec<(Ident, TokenStream)> = variants
+        .iter()
+        .map(|(variant_name, (discriminant, span))| {
+            (variant_name.as_str(), LitByteStr::new(discriminant, *span))
+        })
+        .chain(
+            args.default
+                .iter()
+                .map(|(literal, span)| ("Default", LitByteStr::new(literal, *span))),
+        )
+        .map(|(variant_name, literal)| (format_ident!("{}", variant_name), quote! { #literal }))
+        .collect();
+    let raw_constants = generate_raw_constants(
+        &input.ident,
+        &get_vis(&input.vis),
+        quote! { &'static [u8] },
+        &raws,
+    );
+
+    let mut result = if let Some((literal, span)) = args.default {
         generate_code_default(
             input.ident.to_string(),
             &input.attrs,
             &input.vis,
             variants,
             (literal, span),
+            signatures,
         )
     } else {
-        generate_code(input.ident.to_string(), &input.attrs, &input.vis, variants)
-    }
+        generate_code(
+            input.ident.to_string(),
+            &input.attrs,
+            &input.vis,
+            variants,
+            signatures,
+        )
+    };
+    result.extend(fixed_size);
+    result.extend(iteration);
+    result.extend(raw_constants);
+    result
 }
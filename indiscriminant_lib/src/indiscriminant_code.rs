@@ -0,0 +1,144 @@
+use proc_macro2::TokenStream;
+use quote::*;
+
+use syn::{parse2, Data, DeriveInput, Expr, Ident, Lit};
+
+use crate::get_vis;
+use crate::iteration::generate_iteration;
+
+/// Builds a binary trie over the codes as a table of node pairs, indexed by
+/// the next bit. Positive entries continue at another node, negative entries
+/// are the one's complement of a variant index and zero marks no code
+fn build_decode_table(codes: &[(Ident, String)]) -> Vec<[i32; 2]> {
+    let mut table = vec![[0i32; 2]];
+    for (index, (_, code)) in codes.iter().enumerate() {
+        let mut node = 0;
+        for (depth, bit) in code.bytes().enumerate() {
+            let bit = (bit - b'0') as usize;
+            if depth + 1 == code.len() {
+                table[node][bit] = !(index as i32);
+            } else {
+                if table[node][bit] == 0 {
+                    table.push([0; 2]);
+                    table[node][bit] = (table.len() - 1) as i32;
+                }
+                node = table[node][bit] as usize;
+            }
+        }
+    }
+    table
+}
+
+pub fn indiscriminant_code(args: TokenStream, input: TokenStream) -> TokenStream {
+    assert!(args.is_empty(), "Invalid arguments!");
+
+    // Parse enum body
+    let input = match parse2::<DeriveInput>(input) {
+        Ok(input) => input,
+        Err(err) => return err.to_compile_error(),
+    };
+    let data = match input.data {
+        Data::Enum(data) => data,
+        _ => panic!("Attribute not applied to enum!"),
+    };
+    assert!(!data.variants.is_empty(), "Enum is empty of any variants!");
+
+    // Parse enum variants and their codes
+    let mut codes = Vec::new();
+    for v in data.variants.iter() {
+        let code = match &v.discriminant {
+            Some((_, Expr::Lit(literal))) => match &literal.lit {
+                Lit::Str(s) => s.value(),
+                _ => panic!("Non-string literal found!"),
+            },
+            Some(_) => panic!("Discriminant is not a literal for variant {}!", v.ident),
+            None => panic!("Discriminant not found for variant {}!", v.ident),
+        };
+        assert!(
+            !code.is_empty() && code.len() <= 32 && code.bytes().all(|b| b == b'0' || b == b'1'),
+            "Code for variant {} is not 1 to 32 binary digits!",
+            v.ident
+        );
+        codes.push((v.ident.clone(), code));
+    }
+
+    // No code may start another, or decoding would be ambiguous. Sorted codes
+    // only need each one checked against the next
+    let mut sorted: Vec<&(Ident, String)> = codes.iter().collect();
+    sorted.sort_by(|a, b| a.1.cmp(&b.1));
+    for pair in sorted.windows(2) {
+        assert!(
+            !pair[1].1.starts_with(&pair[0].1),
+            "Code for variant {} is a prefix of the code for variant {}!",
+            pair[0].0,
+            pair[1].0
+        );
+    }
+
+    let name = &input.ident;
+    let vis = get_vis(&input.vis);
+    let attrs = input.attrs.iter().map(|attr| quote! { #attr });
+    let variants: Vec<Ident> = codes.iter().map(|(variant, _)| variant.clone()).collect();
+    let code_quotes = codes.iter().map(|(_, code)| {
+        let (bits, length) = (u32::from_str_radix(code, 2).unwrap(), code.len() as u8);
+        quote! { (#bits, #length) }
+    });
+    let max_length = codes.iter().map(|(_, code)| code.len()).max().unwrap() as u8;
+    let indices = 0..codes.len() as i32;
+    let table = build_decode_table(&codes)
+        .into_iter()
+        .map(|[zero, one]| quote! { [#zero, #one] });
+    let table_len = table.len();
+    let iteration = generate_iteration(name, &vis, &variants);
+
+    quote! {
+        #(#attrs)*
+        #vis enum #name {
+            #(#variants,)*
+        }
+        impl #name {
+            #vis const MAX_CODE_LEN: u8 = #max_length;
+            const DECODE_TABLE: [[i32; 2]; #table_len] = [#(#table),*];
+
+            /// The variant's code right-aligned in the first value, with its
+            /// length in bits in the second
+            #vis const fn code(&self) -> (u32, u8) {
+                match self {
+                    #(#name::#variants => #code_quotes,)*
+                }
+            }
+            /// Writes the variant's code a bit at a time, starting with its
+            /// first digit
+            #vis fn encode(&self, writer: &mut ::indiscriminant::BitWriter) {
+                let (bits, length) = self.code();
+                for i in (0..length).rev() {
+                    writer.write_bits((bits >> i & 1) as u128, 1);
+                }
+            }
+            /// Reads a code a bit at a time through the decode table, leaving
+            /// the reader where it was on error
+            #vis fn decode(
+                reader: &mut ::indiscriminant::BitReader,
+            ) -> Result<Self, ::indiscriminant::ReadError> {
+                let mut cursor = reader.clone();
+                let (mut node, mut bits) = (0, 0);
+                loop {
+                    let bit = cursor.read_bits(1)?;
+                    bits = bits << 1 | bit;
+                    match Self::DECODE_TABLE[node][bit as usize] {
+                        0 => return Err(::indiscriminant::ReadError::InvalidValue(bits)),
+                        next @ 1.. => node = next as usize,
+                        leaf => {
+                            *reader = cursor;
+                            return Ok(match !leaf {
+                                #(#indices => #name::#variants,)*
+                                _ => unreachable!(),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        #iteration
+    }
+}
@@ -0,0 +1,197 @@
+use proc_macro2::{TokenStream, TokenTree};
+use quote::*;
+
+use syn::{parse2, Data, DeriveInput, LitStr};
+
+use crate::indiscriminant_bits::indiscriminant_bits;
+use crate::rename_rule::RenameRule;
+use crate::{get_vis, IntegerType};
+
+pub fn indiscriminant_flags(args: TokenStream, input: TokenStream) -> TokenStream {
+    // Parse integer type
+    let integer_type = match &args.clone().into_iter().collect::<Vec<_>>()[..] {
+        [TokenTree::Ident(ident)] => {
+            IntegerType::from_str(&ident.to_string()).expect("Invalid integer type argument!")
+        }
+        _ => panic!("Invalid arguments!"),
+    };
+    let itype = format_ident!("{}", integer_type.to_str());
+
+    // Parse enum body
+    let parsed = match parse2::<DeriveInput>(input.clone()) {
+        Ok(input) => input,
+        Err(err) => return err.to_compile_error(),
+    };
+    let data = match parsed.data {
+        Data::Enum(data) => data,
+        _ => panic!("Attribute not applied to enum!"),
+    };
+
+    // Each flag is a single bit, named in SCREAMING_SNAKE_CASE when formatted
+    let mut variants = Vec::new();
+    let mut flag_names = Vec::new();
+    for v in data.variants.iter() {
+        let flag = match &v.discriminant {
+            Some((_, expr)) => match integer_type.parse_expr(expr) {
+                Ok(flag) => flag,
+                Err(_) => panic!("Non-integer discriminant found!"),
+            },
+            None => panic!("Discriminant not found for variant {}!", v.ident),
+        };
+        assert!(
+            flag.is_power_of_two(),
+            "Flag {} is not a single bit!",
+            v.ident
+        );
+        let flag_name = RenameRule::ScreamingSnake.apply(&v.ident.to_string());
+        variants.push(v.ident.clone());
+        flag_names.push(LitStr::new(&flag_name, v.ident.span()));
+    }
+
+    // The flags themselves are a plain integer enum
+    let flags_enum = indiscriminant_bits(args, input);
+
+    let name = &parsed.ident;
+    let set = format_ident!("{}Flags", name);
+    let vis = get_vis(&parsed.vis);
+    quote! {
+        #flags_enum
+        /// A set of flags, keeping any bits no flag is defined for
+        #[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+        #vis struct #set(#itype);
+        impl #set {
+            #vis const EMPTY: Self = Self(0);
+            #vis const ALL: Self = Self(0 #(| #name::#variants.to_int())*);
+            #vis const fn from_bits(bits: #itype) -> Self {
+                Self(bits)
+            }
+            #vis const fn bits(&self) -> #itype {
+                self.0
+            }
+            /// The bits set that no flag is defined for
+            #vis const fn unknown_bits(&self) -> #itype {
+                self.0 & !Self::ALL.0
+            }
+            #vis const fn is_empty(&self) -> bool {
+                self.0 == 0
+            }
+            #vis const fn contains(&self, other: Self) -> bool {
+                self.0 & other.0 == other.0
+            }
+            #vis const fn intersects(&self, other: Self) -> bool {
+                self.0 & other.0 != 0
+            }
+            #vis fn insert(&mut self, other: impl Into<Self>) {
+                self.0 |= other.into().0;
+            }
+            #vis fn remove(&mut self, other: impl Into<Self>) {
+                self.0 &= !other.into().0;
+            }
+            #vis fn toggle(&mut self, other: impl Into<Self>) {
+                self.0 ^= other.into().0;
+            }
+            /// The flags set, in order of their bits
+            #vis fn iter(&self) -> impl Iterator<Item = #name> {
+                let bits = self.0;
+                #name::iter().filter(move |flag| bits & flag.to_int() != 0)
+            }
+        }
+        impl From<#name> for #set {
+            fn from(flag: #name) -> Self {
+                Self(flag.to_int())
+            }
+        }
+        impl<T: Into<#set>> ::core::ops::BitOr<T> for #set {
+            type Output = Self;
+            fn bitor(self, other: T) -> Self {
+                Self(self.0 | other.into().0)
+            }
+        }
+        impl<T: Into<#set>> ::core::ops::BitAnd<T> for #set {
+            type Output = Self;
+            fn bitand(self, other: T) -> Self {
+                Self(self.0 & other.into().0)
+            }
+        }
+        impl<T: Into<#set>> ::core::ops::BitXor<T> for #set {
+            type Output = Self;
+            fn bitxor(self, other: T) -> Self {
+                Self(self.0 ^ other.into().0)
+            }
+        }
+        impl<T: Into<#set>> ::core::ops::BitOrAssign<T> for #set {
+            fn bitor_assign(&mut self, other: T) {
+                self.insert(other);
+            }
+        }
+        impl<T: Into<#set>> ::core::ops::BitAndAssign<T> for #set {
+            fn bitand_assign(&mut self, other: T) {
+                self.0 &= other.into().0;
+            }
+        }
+        impl<T: Into<#set>> ::core::ops::BitXorAssign<T> for #set {
+            fn bitxor_assign(&mut self, other: T) {
+                self.toggle(other);
+            }
+        }
+        impl ::core::ops::Not for #set {
+            type Output = Self;
+            fn not(self) -> Self {
+                Self(!self.0)
+            }
+        }
+        impl<T: Into<#set>> ::core::ops::BitOr<T> for #name {
+            type Output = #set;
+            fn bitor(self, other: T) -> #set {
+                #set::from(self) | other
+            }
+        }
+        impl ::core::iter::FromIterator<#name> for #set {
+            fn from_iter<I: IntoIterator<Item = #name>>(iter: I) -> Self {
+                iter.into_iter().fold(Self::EMPTY, |set, flag| set | flag)
+            }
+        }
+        // Flags are written by name, with any unknown bits as one hexadecimal
+        // term and an empty set as 0x0
+        impl ::core::fmt::Display for #set {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                if self.0 == 0 {
+                    return f.write_str("0x0");
+                }
+                let mut separator = "";
+                #(
+                    if self.0 & #name::#variants.to_int() != 0 {
+                        write!(f, "{}{}", separator, #flag_names)?;
+                        separator = " | ";
+                    }
+                )*
+                if self.unknown_bits() != 0 {
+                    write!(f, "{}{:#x}", separator, self.unknown_bits())?;
+                }
+                Ok(())
+            }
+        }
+        impl ::core::fmt::Debug for #set {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                write!(f, "{}({})", stringify!(#set), self)
+            }
+        }
+        impl ::core::str::FromStr for #set {
+            type Err = ::indiscriminant::ParseError;
+            fn from_str(value: &str) -> Result<Self, Self::Err> {
+                let mut bits = 0;
+                for term in value.split('|').map(str::trim) {
+                    bits |= match term {
+                        #(#flag_names => #name::#variants.to_int(),)*
+                        _ => match term.strip_prefix("0x") {
+                            Some(hex) => #itype::from_str_radix(hex, 16)
+                                .map_err(|_| ::indiscriminant::ParseError)?,
+                            None => return Err(::indiscriminant::ParseError),
+                        },
+                    };
+                }
+                Ok(Self(bits))
+            }
+        }
+    }
+}
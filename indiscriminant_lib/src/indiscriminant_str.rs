@@ -1,133 +1,317 @@
+use std::collections::HashSet;
+
 use proc_macro2::{TokenStream, TokenTree};
 use quote::*;
-use std::collections::HashMap;
 
-use syn::{parse2, Attribute, Data, DeriveInput, Expr, Lit, LitStr, Visibility};
+use syn::{
+    parse2, Attribute, Data, DeriveInput, Expr, Fields, Ident, Lit, LitByteStr, LitStr, Visibility,
+};
 
-use crate::get_vis;
+use crate::dispatch::generate_exact_match;
+use crate::glob::{generate_glob_match, generate_glob_prefix, Glob};
+use crate::iteration::generate_iteration;
+use crate::raw::generate_raw_constants;
+use crate::rename_rule::RenameRule;
+use crate::template::generate_code_template;
+use crate::trie::generate_prefix_match;
+use crate::{get_vis, split_args};
 
 type Span = quote::__private::Span;
 
-fn parse_args(args: TokenStream) -> Option<(String, Span)> {
-    // Parse argument list into default string if one is given
-    let mut iter = args.into_iter();
-    match (iter.next(), iter.next(), iter.next(), iter.next()) {
-        (
-            Some(TokenTree::Ident(ident)),
-            Some(TokenTree::Punct(punct)),
-            Some(TokenTree::Literal(literal)),
-            None,
-        ) => {
-            if ident.to_string() != "Default" {
-                panic!("First argument should be Default");
+struct Args {
+    default: Option<(String, Span)>,
+    rename_all: Option<RenameRule>,
+    cstr: bool,
+}
+
+fn parse_args(args: TokenStream) -> Args {
+    // Parse argument list into default string, renaming rule and flags if given
+    let mut result = Args {
+        default: None,
+        rename_all: None,
+        cstr: false,
+    };
+    for arg in split_args(args) {
+        let (ident, literal) = match &arg[..] {
+            [TokenTree::Ident(ident)] if ident == "cstr" => {
+                result.cstr = true;
+                continue;
             }
-            if punct.to_string() != "=" {
-                panic!("Second argument should be =");
+            [TokenTree::Ident(ident), TokenTree::Punct(punct), TokenTree::Literal(literal)]
+                if punct.as_char() == '=' =>
+            {
+                (ident.to_string(), Lit::new(literal.clone()))
             }
-            let s = literal.to_string();
-            if s.len() >= 2 && s.starts_with("\"") && s.ends_with("\"") {
-                let s = s[1..s.len() - 1].to_string();
-                Some((s, Span::from(literal.span())))
-            } else {
-                panic!("Default discriminant not a string!");
+            _ => panic!("Invalid arguments!"),
+        };
+        match (ident.as_str(), literal) {
+            ("Default", Lit::Str(s)) => result.default = Some((s.value(), s.span())),
+            ("Default", _) => panic!("Default discriminant not a string!"),
+            ("rename_all", Lit::Str(s)) => {
+                let rule = RenameRule::from_str(&s.value());
+                result.rename_all =
+                    Some(rule.unwrap_or_else(|| panic!("Unknown rename_all rule {}!", s.value())));
             }
+            ("rename_all", _) => panic!("rename_all rule not a string!"),
+            _ => panic!("Invalid arguments!"),
         }
-        (None, None, None, None) => None,
-        _ => panic!("Invalid arguments!"),
     }
+    result
 }
 
 fn generate_code(
     name: String,
-    attrs: &Vec<Attribute>,
+    attrs: &[Attribute],
     vis: &Visibility,
-    variants: HashMap<String, (String, Span)>,
+    variants: Vec<(String, (String, Span))>,
+    globs: TokenStream,
+    glob_prefix: TokenStream,
 ) -> TokenStream {
     let name = format_ident!("{}", name);
 
-    // Implement functions to convert generated enum to/from Option<&'static str>
+    // Implement const functions to convert generated enum to/from Option<&'static str>,
+    // strings are compared byte by byte since matching on them is not const
     let mut variants_quote = quote!();
     let mut to_quotes = quote!();
-    let mut from_quotes = quote!();
+    let mut from_entries = Vec::new();
+    let mut prefix_entries = Vec::new();
     for (variant_name, (discriminant, span)) in variants {
         let variant_name = format_ident!("{}", variant_name);
         let discriminant = LitStr::new(&discriminant, span);
         variants_quote.extend(quote! { #variant_name, });
         to_quotes.extend(quote! { #name::#variant_name => #discriminant, });
-        from_quotes.extend(quote! { #discriminant => Some(#name::#variant_name), });
+        from_entries.push((
+            discriminant.value().into_bytes(),
+            quote! { Some(#name::#variant_name) },
+        ));
+        prefix_entries.push((
+            discriminant.value().into_bytes(),
+            quote! { #name::#variant_name },
+        ));
     }
-    from_quotes.extend(quote! { _ => None, });
+    let from_match = generate_exact_match(&format_ident!("bytes"), &from_entries);
+
+    let prefix_match = generate_prefix_match(&prefix_entries);
 
     // Construct resulting struct and impl functions
     let vis = get_vis(vis);
     let attrs = attrs.iter().map(|attr| quote! { #attr });
-    TokenStream::from(quote! {
+    quote! {
         #(#attrs)*
         #vis enum #name {
             #variants_quote
         }
         impl #name {
-            #vis fn to_str(&self) -> &'static str {
+            #vis const fn to_str(&self) -> &'static str {
                 match self {
                     #to_quotes
                 }
             }
-            #vis fn from_str(value: &str) -> Option<Self> {
-                match value {
-                    #from_quotes
-                }
+            #vis const fn from_str(value: &str) -> Option<Self> {
+                let bytes = value.as_bytes();
+                #from_match
+                #globs
+                None
+            }
+            #vis fn parse_prefix(value: &str) -> Option<(Self, &str)> {
+                let bytes = value.as_bytes();
+                let mut result: Option<(Self, usize)> = None;
+                #prefix_match
+                #glob_prefix
+                result.map(|(variant, length)| (variant, &value[length..]))
             }
         }
-    })
+    }
 }
 
 fn generate_code_default(
     name: String,
-    attrs: &Vec<Attribute>,
+    attrs: &[Attribute],
     vis: &Visibility,
-    variants: HashMap<String, (String, Span)>,
+    variants: Vec<(String, (String, Span))>,
     default_variant: (String, Span),
+    globs: TokenStream,
+    glob_prefix: TokenStream,
 ) -> TokenStream {
     let name = format_ident!("{}", name);
 
-    // Implement functions to convert generated enum to/from &'static str
+    // Implement const functions to convert generated enum to/from &'static str
     let mut variants_quote = quote!();
     let mut to_quotes = quote!();
-    let mut from_quotes = quote!();
+    let mut from_entries = Vec::new();
+    let mut prefix_entries = Vec::new();
     for (variant_name, (discriminant, span)) in variants {
         let discriminant = LitStr::new(&discriminant, span);
         let variant_name = format_ident!("{}", variant_name);
         variants_quote.extend(quote! { #variant_name, });
         to_quotes.extend(quote! { #name::#variant_name => #discriminant, });
-        from_quotes.extend(quote! { #discriminant => #name::#variant_name, });
+        from_entries.push((
+            discriminant.value().into_bytes(),
+            quote! { #name::#variant_name },
+        ));
+        prefix_entries.push((
+            discriminant.value().into_bytes(),
+            quote! { #name::#variant_name },
+        ));
     }
     let (discriminant, span) = default_variant;
     let discriminant = LitStr::new(&discriminant, span);
     variants_quote.extend(quote! { Default, });
     to_quotes.extend(quote! { #name::Default => #discriminant, });
-    from_quotes.extend(quote! { _ => #name::Default, });
+    let from_match = generate_exact_match(&format_ident!("bytes"), &from_entries);
+
+    let prefix_match = generate_prefix_match(&prefix_entries);
 
     // Construct resulting struct and impl functions
     let vis = get_vis(vis);
     let attrs = attrs.iter().map(|attr| quote! { #attr });
-    TokenStream::from(quote! {
+    quote! {
         #(#attrs)*
         #vis enum #name {
             #variants_quote
         }
         impl #name {
-            #vis fn to_str(&self) -> &'static str {
+            #vis const fn to_str(&self) -> &'static str {
+                match self {
+                    #to_quotes
+                }
+            }
+            #vis const fn from_str(value: &str) -> Self {
+                let bytes = value.as_bytes();
+                #from_match
+                #globs
+                #name::Default
+            }
+            #vis fn parse_prefix(value: &str) -> Option<(Self, &str)> {
+                let bytes = value.as_bytes();
+                let mut result: Option<(Self, usize)> = None;
+                #prefix_match
+                #glob_prefix
+                result.map(|(variant, length)| (variant, &value[length..]))
+            }
+        }
+    }
+}
+
+fn generate_code_cstr(
+    name: String,
+    vis: &Visibility,
+    variants: &[(String, (String, Span))],
+    default_variant: Option<&(String, Span)>,
+    has_globs: bool,
+) -> TokenStream {
+    let name = format_ident!("{}", name);
+
+    // Implement functions to convert generated enum to/from &'static CStr
+    let mut to_quotes = quote!();
+    let mut from_quotes = quote!();
+    for (variant_name, (discriminant, span)) in variants {
+        let variant_name = format_ident!("{}", variant_name);
+        let with_nul = LitByteStr::new(format!("{}\0", discriminant).as_bytes(), *span);
+        let discriminant = LitByteStr::new(discriminant.as_bytes(), *span);
+        to_quotes.extend(quote! { #name::#variant_name => #with_nul, });
+        if default_variant.is_some() {
+            from_quotes.extend(quote! { #discriminant => #name::#variant_name, });
+        } else {
+            from_quotes.extend(quote! { #discriminant => Some(#name::#variant_name), });
+        }
+    }
+    let (from_type, fallback) = if let Some((discriminant, span)) = default_variant {
+        let with_nul = LitByteStr::new(format!("{}\0", discriminant).as_bytes(), *span);
+        to_quotes.extend(quote! { #name::Default => #with_nul, });
+        (quote! { Self }, quote! { #name::Default })
+    } else {
+        (quote! { Option<Self> }, quote! { None })
+    };
+    // Glob patterns are matched by from_str, which needs the value as a str
+    if has_globs {
+        from_quotes.extend(quote! {
+            _ => match value.to_str() {
+                Ok(value) => Self::from_str(value),
+                Err(_) => #fallback,
+            },
+        });
+    } else {
+        from_quotes.extend(quote! { _ => #fallback, });
+    }
+
+    let vis = get_vis(vis);
+    quote! {
+        impl #name {
+            #vis fn to_cstr(&self) -> &'static ::core::ffi::CStr {
+                let bytes: &'static [u8] = match self {
+                    #to_quotes
+                };
+                // SAFETY: Discriminants were checked for interior NULs when the
+                // enum was expanded and each one is given a single NUL terminator
+                unsafe { ::core::ffi::CStr::from_bytes_with_nul_unchecked(bytes) }
+            }
+            #vis fn from_cstr(value: &::core::ffi::CStr) -> #from_type {
+                match value.to_bytes() {
+                    #from_quotes
+                }
+            }
+        }
+    }
+}
+
+fn generate_code_utf16(
+    name: String,
+    vis: &Visibility,
+    variants: &[(String, (String, Span))],
+    default_variant: Option<&(String, Span)>,
+    has_globs: bool,
+) -> TokenStream {
+    let name = format_ident!("{}", name);
+
+    // Implement functions to convert generated enum to/from &'static [u16]
+    let mut to_quotes = quote!();
+    let mut from_quotes = quote!();
+    for (variant_name, (discriminant, _)) in variants {
+        let variant_name = format_ident!("{}", variant_name);
+        let units = discriminant.encode_utf16();
+        to_quotes.extend(quote! { #name::#variant_name => &[#(#units),*], });
+        let units = discriminant.encode_utf16();
+        if default_variant.is_some() {
+            from_quotes.extend(quote! { [#(#units),*] => #name::#variant_name, });
+        } else {
+            from_quotes.extend(quote! { [#(#units),*] => Some(#name::#variant_name), });
+        }
+    }
+    let (from_type, fallback) = if let Some((discriminant, _)) = default_variant {
+        let units = discriminant.encode_utf16();
+        to_quotes.extend(quote! { #name::Default => &[#(#units),*], });
+        (quote! { Self }, quote! { #name::Default })
+    } else {
+        (quote! { Option<Self> }, quote! { None })
+    };
+    // Glob patterns are matched by from_str, which needs the value decoded
+    if has_globs {
+        from_quotes.extend(quote! {
+            _ => match ::indiscriminant::__private::decode_utf16(value) {
+                Some(value) => Self::from_str(&value),
+                None => #fallback,
+            },
+        });
+    } else {
+        from_quotes.extend(quote! { _ => #fallback, });
+    }
+
+    let vis = get_vis(vis);
+    quote! {
+        impl #name {
+            #vis fn to_utf16(&self) -> &'static [u16] {
                 match self {
                     #to_quotes
                 }
             }
-            #vis fn from_str(value: &str) -> Self {
+            #vis fn from_utf16(value: &[u16]) -> #from_type {
                 match value {
                     #from_quotes
                 }
             }
         }
-    })
+    }
 }
 
 pub fn indiscriminant_str(args: TokenStream, input: TokenStream) -> TokenStream {
@@ -137,19 +321,42 @@ pub fn indiscriminant_str(args: TokenStream, input: TokenStream) -> TokenStream
     // Parse enum body
     let input = match parse2::<DeriveInput>(input) {
         Ok(input) => input,
-        Err(err) => return TokenStream::from(err.to_compile_error()),
+        Err(err) => return err.to_compile_error(),
     };
     let data = match input.data {
         Data::Enum(data) => data,
         _ => panic!("Attribute not applied to enum!"),
     };
-    assert!(data.variants.len() > 0, "Enum is empty of any variants!");
+    assert!(!data.variants.is_empty(), "Enum is empty of any variants!");
+
+    // Data-carrying variants are formatted and parsed through templates instead
+    if data
+        .variants
+        .iter()
+        .any(|v| !matches!(v.fields, Fields::Unit))
+    {
+        assert!(
+            !args.cstr,
+            "C strings are not supported with template variants!"
+        );
+        return generate_code_template(
+            &input.ident,
+            &input.attrs,
+            &input.vis,
+            &input.generics,
+            &data,
+            args.rename_all,
+            args.default,
+        );
+    }
 
     // Parse enum variants and discriminants
-    let mut variants = HashMap::new();
-    let mut discriminants = Vec::new();
-    let has_default = if let Some((literal, _)) = &args {
-        discriminants.push(literal.clone());
+    let mut variants = Vec::new();
+    let mut discriminants = HashSet::new();
+    let mut all_globs = Vec::new();
+    let mut glob_checks = quote!();
+    let has_default = if let Some((literal, _)) = &args.default {
+        discriminants.insert(literal.clone());
         true
     } else {
         false
@@ -157,34 +364,131 @@ pub fn indiscriminant_str(args: TokenStream, input: TokenStream) -> TokenStream
 
     for v in data.variants.iter() {
         let ident = v.ident.to_string();
-        let literal = match (ident.as_str(), &v.discriminant) {
+        let globs = Glob::parse(&v.ident, &v.attrs);
+        let (discriminant, span) = match (ident.as_str(), &v.discriminant) {
             ("Default", _) if has_default => {
                 panic!("Default variant already provided as argument!")
             }
-            (_, Some((_, Expr::Lit(literal)))) => literal,
+            (_, Some((_, Expr::Lit(literal)))) => match &literal.lit {
+                Lit::Str(b) => (b.value(), b.span()),
+                _ => panic!("Non-string literal found!"),
+            },
             (ident, Some(_)) => panic!("Discriminant is not a literal for variant {}!", ident),
-            (ident, None) => panic!("Discriminant not found for variant {}!", ident),
+            // Generate the discriminant from the variant name if a rule was
+            // given, or from its glob pattern otherwise
+            (ident, None) => match (&args.rename_all, Glob::canonical(&globs)) {
+                (Some(rule), _) => (rule.apply(ident), v.ident.span()),
+                (None, Some(canonical)) => (canonical, v.ident.span()),
+                (None, None) => panic!("Discriminant not found for variant {}!", ident),
+            },
         };
-        let (discriminant, span) = match &literal.lit {
-            Lit::Str(b) => (b.value(), b.span()),
-            _ => panic!("Non-string literal found!"),
+        let message = match (&v.discriminant, &args.rename_all) {
+            (None, None) => format!(
+                "Glob pattern needs an explicit discriminant for variant {}!",
+                ident
+            ),
+            _ => format!(
+                "Discriminant for variant {} matches none of its glob patterns!",
+                ident
+            ),
         };
-        match discriminants.binary_search(&discriminant) {
-            Ok(_) => panic!("Duplicate discriminants found!"),
-            Err(pos) => discriminants.insert(pos, discriminant.clone()),
+        glob_checks.extend(Glob::generate_check(&globs, &discriminant, &message));
+        assert!(
+            discriminants.insert(discriminant.clone()),
+            "Duplicate discriminants found!"
+        );
+        variants.push((ident.to_string(), (discriminant, span)));
+        all_globs.extend(globs);
+    }
+    let has_globs = !all_globs.is_empty();
+    let globs = generate_glob_match(&input.ident, &all_globs, has_default);
+    let glob_prefix = generate_glob_prefix(&input.ident, &all_globs);
+
+    // C strings end at the first NUL, so one inside would truncate the discriminant
+    let cstr = args.cstr.then(|| {
+        for (variant_name, (discriminant, _)) in &variants {
+            assert!(
+                !discriminant.contains('\0'),
+                "Discriminant for variant {} contains an interior NUL!",
+                variant_name
+            );
+        }
+        if let Some((literal, _)) = &args.default {
+            assert!(
+                !literal.contains('\0'),
+                "Default discriminant contains an interior NUL!"
+            );
         }
-        variants.insert(ident.to_string(), (discriminant, span));
+        generate_code_cstr(
+            input.ident.to_string(),
+            &input.vis,
+            &variants,
+            args.default.as_ref(),
+            has_globs,
+        )
+    });
+
+    // Variants are listed and stepped through in declaration order
+    let mut order: Vec<Ident> = variants
+        .iter()
+        .map(|(variant_name, _)| format_ident!("{}", variant_name))
+        .collect();
+    if args.default.is_some() {
+        order.push(format_ident!("Default"));
     }
+    let iteration = generate_iteration(&input.ident, &get_vis(&input.vis), &order);
+
+    let raws: Vec<(Ident, TokenStream)> = variants
+        .iter()
+        .map(|(variant_name, (discriminant, span))| {
+            (variant_name.as_str(), LitStr::new(discriminant, *span))
+        })
+        .chain(
+            args.default
+                .iter()
+                .map(|(literal, span)| ("Default", LitStr::new(literal, *span))),
+        )
+        .map(|(variant_name, literal)| (format_ident!("{}", variant_name), quote! { #literal }))
+        .collect();
+    let raw_constants = generate_raw_constants(
+        &input.ident,
+        &get_vis(&input.vis),
+        quote! { &'static str },
+        &raws,
+    );
 
-    if let Some((literal, span)) = args {
+    let utf16 = generate_code_utf16(
+        input.ident.to_string(),
+        &input.vis,
+        &variants,
+        args.default.as_ref(),
+        has_globs,
+    );
+
+    let mut result = if let Some((literal, span)) = args.default {
         generate_code_default(
             input.ident.to_string(),
             &input.attrs,
             &input.vis,
             variants,
             (literal, span),
+            globs,
+            glob_prefix,
         )
     } else {
-        generate_code(input.ident.to_string(), &input.attrs, &input.vis, variants)
-    }
+        generate_code(
+            input.ident.to_string(),
+            &input.attrs,
+            &input.vis,
+            variants,
+            globs,
+            glob_prefix,
+        )
+    };
+    result.extend(utf16);
+    result.extend(cstr);
+    result.extend(iteration);
+    result.extend(raw_constants);
+    result.extend(glob_checks);
+    result
 }
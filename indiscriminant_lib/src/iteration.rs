@@ -0,0 +1,44 @@
+use proc_macro2::{Ident, TokenStream};
+use quote::*;
+
+/// Generates `ALL`, `COUNT` and `iter` along with stepping between
+/// neighbouring variants, all following the given order
+pub(crate) fn generate_iteration(name: &Ident, vis: &TokenStream, order: &[Ident]) -> TokenStream {
+    let count = order.len();
+    let next = order
+        .iter()
+        .skip(1)
+        .map(|variant| quote! { Some(#name::#variant) })
+        .chain([quote! { None }]);
+    let prev = [quote! { None }].into_iter().chain(
+        order[..count - 1]
+            .iter()
+            .map(|variant| quote! { Some(#name::#variant) }),
+    );
+    let cycle_next = order[1..].iter().chain(&order[..1]);
+
+    quote! {
+        impl #name {
+            #vis const COUNT: usize = #count;
+            #vis const ALL: [Self; #count] = [#(#name::#order),*];
+            #vis fn iter() -> ::core::array::IntoIter<Self, #count> {
+                Self::ALL.into_iter()
+            }
+            #vis fn next(&self) -> Option<Self> {
+                match self {
+                    #(#name::#order => #next,)*
+                }
+            }
+            #vis fn prev(&self) -> Option<Self> {
+                match self {
+                    #(#name::#order => #prev,)*
+                }
+            }
+            #vis fn cycle_next(&self) -> Self {
+                match self {
+                    #(#name::#order => #name::#cycle_next,)*
+                }
+            }
+        }
+    }
+}
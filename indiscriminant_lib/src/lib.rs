@@ -1,7 +1,21 @@
 pub mod indiscriminant_bits;
 pub mod indiscriminant_byte_str;
+pub mod indiscriminant_code;
+pub mod indiscriminant_flags;
 pub mod indiscriminant_str;
 
+mod dispatch;
+mod encoding;
+mod glob;
+mod iteration;
+mod raw;
+mod recognizer;
+mod rename_rule;
+mod sniff;
+mod template;
+mod trie;
+
+use proc_macro2::{TokenStream, TokenTree};
 use quote::*;
 
 use syn::{Expr, Lit, Visibility};
@@ -16,6 +30,19 @@ pub(crate) fn get_vis(vis: &Visibility) -> QuoteResult {
     }
 }
 
+/// Splits a macro argument list on its top-level commas
+pub(crate) fn split_args(args: TokenStream) -> Vec<Vec<TokenTree>> {
+    let mut result = vec![Vec::new()];
+    for token in args {
+        match token {
+            TokenTree::Punct(punct) if punct.as_char() == ',' => result.push(Vec::new()),
+            token => result.last_mut().unwrap().push(token),
+        }
+    }
+    result.retain(|arg| !arg.is_empty());
+    result
+}
+
 #[derive(PartialEq, Clone)]
 enum IntegerType {
     U8,
@@ -46,7 +73,7 @@ impl IntegerType {
         }
     }
 
-    fn from_expr(&self, expr: &Expr) -> Result<usize, ()> {
+    fn parse_expr(&self, expr: &Expr) -> Result<usize, ()> {
         match expr {
             Expr::Lit(lit) => match &lit.lit {
                 Lit::Byte(b) => Ok(b.value() as usize),
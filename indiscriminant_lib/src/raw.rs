@@ -0,0 +1,36 @@
+use std::collections::HashSet;
+
+use proc_macro2::{Ident, TokenStream};
+use quote::*;
+
+use crate::rename_rule::RenameRule;
+
+/// Generates a `RAW_` constant of type `ty` holding each variant's raw
+/// discriminant, named after the variant in SCREAMING_SNAKE_CASE
+pub(crate) fn generate_raw_constants(
+    name: &Ident,
+    vis: &TokenStream,
+    ty: TokenStream,
+    raws: &[(Ident, TokenStream)],
+) -> TokenStream {
+    let mut names = HashSet::new();
+    let mut constants = quote!();
+    for (variant, raw) in raws {
+        let constant = format!(
+            "RAW_{}",
+            RenameRule::ScreamingSnake.apply(&variant.to_string())
+        );
+        let ident = format_ident!("{}", constant);
+        assert!(
+            names.insert(constant),
+            "Raw constant {} generated for more than one variant!",
+            ident
+        );
+        constants.extend(quote! { #vis const #ident: #ty = #raw; });
+    }
+    quote! {
+        impl #name {
+            #constants
+        }
+    }
+}
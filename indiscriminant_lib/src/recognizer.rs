@@ -0,0 +1,104 @@
+use proc_macro2::{Ident, TokenStream};
+use quote::*;
+
+struct State {
+    variant: Option<TokenStream>,
+    transitions: Vec<(u8, usize)>,
+}
+
+/// Numbers the nodes of a byte trie over the discriminants, the root being
+/// state zero
+fn build_states(entries: &[(Vec<u8>, TokenStream)]) -> Vec<State> {
+    let mut states = vec![State {
+        variant: None,
+        transitions: Vec::new(),
+    }];
+    for (bytes, variant) in entries {
+        let mut state = 0;
+        for byte in bytes {
+            let next = states[state]
+                .transitions
+                .iter()
+                .find(|(b, _)| b == byte)
+                .map(|(_, next)| *next);
+            state = match next {
+                Some(next) => next,
+                None => {
+                    states.push(State {
+                        variant: None,
+                        transitions: Vec::new(),
+                    });
+                    let next = states.len() - 1;
+                    states[state].transitions.push((*byte, next));
+                    next
+                }
+            };
+        }
+        states[state].variant = Some(variant.clone());
+    }
+    states
+}
+
+/// Generates a recognizer struct that walks the discriminant trie one byte at
+/// a time, so input can be fed in arbitrary chunks without buffering
+pub(crate) fn generate_recognizer(
+    name: &Ident,
+    vis: &TokenStream,
+    entries: Vec<(Vec<u8>, TokenStream)>,
+) -> TokenStream {
+    let recognizer = format_ident!("{}Recognizer", name);
+    let states = build_states(&entries);
+
+    let mut transitions = quote!();
+    let mut matched = quote!();
+    let mut pending = quote!();
+    let mut finished = quote!();
+    for (index, state) in states.iter().enumerate() {
+        for (byte, next) in &state.transitions {
+            transitions.extend(quote! { (#index, #byte) => #next, });
+        }
+        // A variant is only final once no longer discriminant can follow it
+        match (&state.variant, state.transitions.is_empty()) {
+            (Some(variant), true) => matched
+                .extend(quote! { #index => ::indiscriminant::Recognized::Matched(#variant), }),
+            _ => pending.extend(quote! { #index => ::indiscriminant::Recognized::NeedMore, }),
+        }
+        if let Some(variant) = &state.variant {
+            finished.extend(quote! { #index => Some(#variant), });
+        }
+    }
+
+    quote! {
+        #[derive(Clone, Copy, Default)]
+        #vis struct #recognizer {
+            state: usize,
+        }
+        impl #recognizer {
+            #vis fn new() -> Self {
+                Self { state: 0 }
+            }
+            #vis fn feed(&mut self, chunk: &[u8]) -> ::indiscriminant::Recognized<#name> {
+                for byte in chunk {
+                    if self.state == usize::MAX {
+                        break;
+                    }
+                    self.state = match (self.state, *byte) {
+                        #transitions
+                        _ => usize::MAX,
+                    };
+                }
+                match self.state {
+                    #matched
+                    #pending
+                    _ => ::indiscriminant::Recognized::NoMatch,
+                }
+            }
+            #vis fn finish(&self) -> Option<#name> {
+                match self.state {
+                    #finished
+                    _ => None,
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,52 @@
+/// Case conversions applied to variant names when a string discriminant is
+/// left out and `rename_all` is given
+#[derive(PartialEq, Clone, Copy)]
+pub(crate) enum RenameRule {
+    Lower,
+    Snake,
+    Kebab,
+    ScreamingSnake,
+    Camel,
+}
+
+impl RenameRule {
+    pub(crate) fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "lowercase" => Some(Self::Lower),
+            "snake_case" => Some(Self::Snake),
+            "kebab-case" => Some(Self::Kebab),
+            "SCREAMING_SNAKE_CASE" => Some(Self::ScreamingSnake),
+            "camelCase" => Some(Self::Camel),
+            _ => None,
+        }
+    }
+
+    /// Converts a PascalCase variant name, every uppercase letter after the
+    /// first starts a new word
+    pub(crate) fn apply(&self, variant: &str) -> String {
+        match self {
+            Self::Lower => variant.to_ascii_lowercase(),
+            Self::Snake => Self::separate(variant, '_'),
+            Self::Kebab => Self::separate(variant, '-'),
+            Self::ScreamingSnake => Self::separate(variant, '_').to_ascii_uppercase(),
+            Self::Camel => {
+                let mut chars = variant.chars();
+                match chars.next() {
+                    Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+                    None => String::new(),
+                }
+            }
+        }
+    }
+
+    fn separate(variant: &str, separator: char) -> String {
+        let mut result = String::new();
+        for (i, c) in variant.chars().enumerate() {
+            if i > 0 && c.is_ascii_uppercase() {
+                result.push(separator);
+            }
+            result.push(c.to_ascii_lowercase());
+        }
+        result
+    }
+}
@@ -0,0 +1,171 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::*;
+
+use syn::{Attribute, Lit, LitByteStr, Meta, NestedMeta};
+
+/// A byte signature that identifies a variant somewhere in its input
+pub(crate) struct Signature {
+    variant: Ident,
+    signature: Vec<u8>,
+    offset: usize,
+    mask: Vec<u8>,
+}
+
+impl Signature {
+    /// Parses an optional `#[sniff(signature = b"..", offset = N, mask = b"..")]`
+    /// attribute, the signature defaulting to the discriminant at offset zero
+    pub(crate) fn parse(variant: &Ident, attrs: &[Attribute], discriminant: &[u8]) -> Self {
+        let mut signature = discriminant.to_vec();
+        let mut offset = 0;
+        let mut mask = None;
+        for attr in attrs.iter().filter(|attr| attr.path.is_ident("sniff")) {
+            let list = match attr.parse_meta() {
+                Ok(Meta::List(list)) => list,
+                _ => panic!("Invalid sniff attribute for variant {}!", variant),
+            };
+            for nested in list.nested {
+                let (ident, lit) = match nested {
+                    NestedMeta::Meta(Meta::NameValue(nv)) => match nv.path.get_ident() {
+                        Some(ident) => (ident.to_string(), nv.lit),
+                        None => panic!("Invalid sniff attribute for variant {}!", variant),
+                    },
+                    _ => panic!("Invalid sniff attribute for variant {}!", variant),
+                };
+                match (ident.as_str(), lit) {
+                    ("signature", Lit::ByteStr(b)) => signature = b.value(),
+                    ("mask", Lit::ByteStr(b)) => mask = Some(b.value()),
+                    ("offset", Lit::Int(i)) => {
+                        offset = i.base10_parse::<usize>().expect("Invalid sniff offset!")
+                    }
+                    _ => panic!("Invalid sniff attribute for variant {}!", variant),
+                }
+            }
+        }
+        let mask = mask.unwrap_or_else(|| vec![0xff; signature.len()]);
+        assert!(
+            mask.len() == signature.len(),
+            "Sniff mask and signature lengths differ for variant {}!",
+            variant
+        );
+        // Bits outside the mask never take part in a comparison
+        let signature = signature.iter().zip(&mask).map(|(s, m)| s & m).collect();
+        Self {
+            variant: variant.clone(),
+            signature,
+            offset,
+            mask,
+        }
+    }
+
+    fn specificity(&self) -> u32 {
+        self.mask.iter().map(|m| m.count_ones()).sum()
+    }
+
+    /// Checks if some input could match both signatures
+    fn overlaps(&self, other: &Self) -> bool {
+        let start = self.offset.max(other.offset);
+        let end = (self.offset + self.mask.len()).min(other.offset + other.mask.len());
+        (start..end).all(|i| {
+            let (a, b) = (i - self.offset, i - other.offset);
+            let common = self.mask[a] & other.mask[b];
+            self.signature[a] & common == other.signature[b] & common
+        })
+    }
+}
+
+/// Generates `sniff`, which checks signatures from most to least specific
+/// (counting the bits compared) and falls back to declaration order, noting
+/// any signatures where only declaration order decides the result
+pub(crate) fn generate_sniff(
+    name: &Ident,
+    vis: &TokenStream,
+    mut signatures: Vec<Signature>,
+) -> TokenStream {
+    // Stable sort keeps declaration order between equally specific signatures
+    signatures.sort_by_key(|s| std::cmp::Reverse(s.specificity()));
+
+    // Only equally specific signatures can leave declaration order to decide.
+    // Signatures comparing every bit from offset zero only overlap when equal,
+    // so those are found by hashing and just the rest compared to each other
+    let mut ambiguous = Vec::new();
+    let mut group_start = 0;
+    let mut full = HashMap::new();
+    let mut partial: Vec<&Signature> = Vec::new();
+    for (i, s) in signatures.iter().enumerate() {
+        if signatures[group_start].specificity() != s.specificity() {
+            group_start = i;
+            full.clear();
+            partial.clear();
+        }
+        if s.offset == 0 && s.mask.iter().all(|m| *m == 0xff) {
+            match full.entry(&s.signature[..]) {
+                Entry::Occupied(earlier) => ambiguous.push((*earlier.get(), s)),
+                Entry::Vacant(entry) => {
+                    entry.insert(s);
+                }
+            }
+            for earlier in partial.iter().copied() {
+                if s.overlaps(earlier) {
+                    ambiguous.push((earlier, s));
+                }
+            }
+        } else {
+            for earlier in &signatures[group_start..i] {
+                if earlier.overlaps(s) {
+                    ambiguous.push((earlier, s));
+                }
+            }
+            partial.push(s);
+        }
+    }
+
+    let mut notes = quote!();
+    for (earlier, s) in ambiguous {
+        let note = format!(
+            "Sniff signatures of {} and {} are ambiguous, {} takes priority",
+            earlier.variant, s.variant, earlier.variant
+        );
+        notes.extend(quote_spanned! { s.variant.span() =>
+            const _: () = {
+                #[deprecated(note = #note)]
+                struct AmbiguousSignature;
+                let _ = AmbiguousSignature;
+            };
+        });
+    }
+
+    let mut checks = quote!();
+    for s in signatures.iter() {
+        let variant = &s.variant;
+        let (start, end) = (s.offset, s.offset + s.signature.len());
+        let signature = LitByteStr::new(&s.signature, Span::call_site());
+        if s.mask.iter().all(|m| *m == 0xff) {
+            checks.extend(quote! {
+                if bytes.get(#start..#end) == Some(&#signature[..]) {
+                    return Some(#name::#variant);
+                }
+            });
+        } else {
+            let mask = LitByteStr::new(&s.mask, Span::call_site());
+            checks.extend(quote! {
+                if let Some(window) = bytes.get(#start..#end) {
+                    let masked = window.iter().zip(#mask.iter()).map(|(b, m)| b & m);
+                    if masked.eq(#signature.iter().copied()) {
+                        return Some(#name::#variant);
+                    }
+                }
+            });
+        }
+    }
+
+    quote! {
+        #vis fn sniff(bytes: &[u8]) -> Option<Self> {
+            #notes
+            #checks
+            None
+        }
+    }
+}
@@ -0,0 +1,278 @@
+use std::collections::HashSet;
+
+use proc_macro2::{Ident, Span, TokenStream, TokenTree};
+use quote::*;
+
+use syn::{
+    parse_quote, Attribute, DataEnum, Expr, Fields, Generics, Lit, LitStr, Type, Visibility,
+};
+
+use crate::get_vis;
+use crate::rename_rule::RenameRule;
+
+enum Segment {
+    Literal(String),
+    Field(String),
+}
+
+/// Splits a template such as `"rgb({r},{g},{b})"` into literal text and
+/// field placeholders, with `{{` and `}}` escaping braces like `format!`
+fn parse_template(template: &str, variant: &Ident) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut literal = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('{', Some('{')) | ('}', Some('}')) => {
+                literal.push(c);
+                chars.next();
+            }
+            ('{', _) => {
+                let mut field = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) if c.is_alphanumeric() || c == '_' => field.push(c),
+                        _ => panic!("Invalid placeholder in template for variant {}!", variant),
+                    }
+                }
+                if !literal.is_empty() {
+                    segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                } else if let Some(Segment::Field(_)) = segments.last() {
+                    panic!("Adjacent placeholders in template for variant {}!", variant);
+                }
+                segments.push(Segment::Field(field));
+            }
+            ('}', _) => panic!("Unmatched }} in template for variant {}!", variant),
+            _ => literal.push(c),
+        }
+    }
+    if !literal.is_empty() {
+        segments.push(Segment::Literal(literal));
+    }
+    segments
+}
+
+/// Generates the parsing and formatting code for one data-carrying variant,
+/// whose fields are each parsed with their own `FromStr`
+fn generate_variant(
+    name: &Ident,
+    variant: &Ident,
+    fields: &Fields,
+    template: &LitStr,
+) -> (TokenStream, TokenStream) {
+    // Fields are bound as field_0, field_1, .. in declaration order
+    let fields: Vec<(String, Option<&Ident>, &Type)> = fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| match &field.ident {
+            Some(ident) => (ident.to_string(), Some(ident), &field.ty),
+            None => (i.to_string(), None, &field.ty),
+        })
+        .collect();
+    let bindings: Vec<Ident> = (0..fields.len())
+        .map(|i| format_ident!("field_{}", i))
+        .collect();
+    let field_index = |field: &str| match fields.iter().position(|(f, _, _)| f == field) {
+        Some(index) => index,
+        None => panic!(
+            "Unknown field {} in template for variant {}!",
+            field, variant
+        ),
+    };
+
+    // Consume literal text and split fields off the front of the input
+    let segments = parse_template(&template.value(), variant);
+    let mut parse = quote! { let rest = input; };
+    let mut used = vec![false; fields.len()];
+    let mut i = 0;
+    while i < segments.len() {
+        match (&segments[i], segments.get(i + 1)) {
+            (Segment::Literal(literal), _) => {
+                parse.extend(quote! { let rest = rest.strip_prefix(#literal)?; });
+                i += 1;
+            }
+            (Segment::Field(field), next) => {
+                let index = field_index(field);
+                assert!(
+                    !used[index],
+                    "Field {} used twice in template for variant {}!",
+                    field, variant
+                );
+                used[index] = true;
+                let (binding, ty) = (&bindings[index], fields[index].2);
+                let text = match next {
+                    Some(Segment::Literal(literal)) if i + 2 == segments.len() => {
+                        quote! { rest.strip_suffix(#literal)? }
+                    }
+                    Some(Segment::Literal(literal)) => {
+                        parse.extend(quote! { let (text, rest) = rest.split_once(#literal)?; });
+                        quote! { text }
+                    }
+                    _ => quote! { rest },
+                };
+                parse.extend(quote! { let #binding: #ty = #text.parse().ok()?; });
+                i += 2;
+            }
+        }
+    }
+    assert!(
+        used.iter().all(|used| *used),
+        "Template for variant {} does not use every field!",
+        variant
+    );
+
+    // Formatting reuses the template as the format string
+    let (pattern, construct, format) = match fields.first() {
+        Some((_, Some(_), _)) => {
+            let idents: Vec<&Ident> = fields.iter().map(|(_, ident, _)| ident.unwrap()).collect();
+            (
+                quote! { #name::#variant { #(#idents: #bindings),* } },
+                quote! { #name::#variant { #(#idents: #bindings),* } },
+                quote! { #(#idents = #bindings),* },
+            )
+        }
+        _ => (
+            quote! { #name::#variant(#(#bindings),*) },
+            quote! { #name::#variant(#(#bindings),*) },
+            quote! { #(#bindings),* },
+        ),
+    };
+    let parser = format_ident!("parse_{}", variant);
+    (
+        quote! {
+            // A closure rather than a nested function, so it can use the
+            // enum's generics
+            #[allow(non_snake_case)]
+            let #parser = |input: &str| -> Option<Self> {
+                #parse
+                Some(#construct)
+            };
+            if let Some(value) = #parser(value) {
+                return Ok(value);
+            }
+        },
+        quote! { #pattern => write!(f, #template, #format), },
+    )
+}
+
+/// Whether `tokens` mention any of the generic type parameters `params`
+fn uses_params(tokens: TokenStream, params: &[&Ident]) -> bool {
+    tokens.into_iter().any(|token| match token {
+        TokenTree::Ident(ident) => params.contains(&&ident),
+        TokenTree::Group(group) => uses_params(group.stream(), params),
+        _ => false,
+    })
+}
+
+/// Generates `Display` and `FromStr` for string enums with data-carrying
+/// variants, whose discriminants are templates with `{field}` placeholders
+pub(crate) fn generate_code_template(
+    name: &Ident,
+    attrs: &[Attribute],
+    vis: &Visibility,
+    generics: &Generics,
+    data: &DataEnum,
+    rename_all: Option<RenameRule>,
+    default_variant: Option<(String, Span)>,
+) -> TokenStream {
+    let mut variants_quote = quote!();
+    let mut exact_quotes = quote!();
+    let mut template_quotes = quote!();
+    let mut display_quotes = quote!();
+    let mut discriminants = HashSet::new();
+    // Fields of generic types need the traits the impls use on them
+    let params: Vec<&Ident> = generics.type_params().map(|p| &p.ident).collect();
+    let mut display_generics = generics.clone();
+    let mut from_str_generics = generics.clone();
+    let mut bounded = HashSet::new();
+    for v in data.variants.iter() {
+        let variant = &v.ident;
+        assert!(
+            !v.attrs.iter().any(|attr| attr.path.is_ident("glob")),
+            "Glob patterns are not supported with template variants!"
+        );
+        if default_variant.is_some() && variant == "Default" {
+            panic!("Default variant already provided as argument!");
+        }
+        let literal = match (&v.discriminant, rename_all, &v.fields) {
+            (Some((_, Expr::Lit(literal))), _, _) => match &literal.lit {
+                Lit::Str(s) => s.clone(),
+                _ => panic!("Non-string literal found!"),
+            },
+            (None, Some(rule), Fields::Unit) => {
+                LitStr::new(&rule.apply(&variant.to_string()), variant.span())
+            }
+            _ => panic!("Discriminant not found for variant {}!", variant),
+        };
+        assert!(
+            discriminants.insert(literal.value()),
+            "Duplicate discriminants found!"
+        );
+
+        let (variant_attrs, fields) = (&v.attrs, &v.fields);
+        variants_quote.extend(quote! { #(#variant_attrs)* #variant #fields, });
+        for field in fields.iter() {
+            let ty = &field.ty;
+            if uses_params(ty.to_token_stream(), &params)
+                && bounded.insert(ty.to_token_stream().to_string())
+            {
+                display_generics
+                    .make_where_clause()
+                    .predicates
+                    .push(parse_quote! { #ty: ::core::fmt::Display });
+                from_str_generics
+                    .make_where_clause()
+                    .predicates
+                    .push(parse_quote! { #ty: ::core::str::FromStr });
+            }
+        }
+        if let Fields::Unit = fields {
+            exact_quotes.extend(quote! { #literal => return Ok(#name::#variant), });
+            display_quotes.extend(quote! { #name::#variant => f.write_str(#literal), });
+        } else {
+            let (parse, display) = generate_variant(name, variant, fields, &literal);
+            template_quotes.extend(parse);
+            display_quotes.extend(display);
+        }
+    }
+    let fallback = if let Some((discriminant, span)) = default_variant {
+        let literal = LitStr::new(&discriminant, span);
+        variants_quote.extend(quote! { Default, });
+        display_quotes.extend(quote! { #name::Default => f.write_str(#literal), });
+        quote! { Ok(#name::Default) }
+    } else {
+        quote! { Err(::indiscriminant::ParseError) }
+    };
+
+    // Construct resulting enum, keeping its generics, and trait impls
+    let vis = get_vis(vis);
+    let attrs = attrs.iter().map(|attr| quote! { #attr });
+    let (_, ty_generics, where_clause) = generics.split_for_impl();
+    let (display_generics, _, display_where) = display_generics.split_for_impl();
+    let (from_str_generics, _, from_str_where) = from_str_generics.split_for_impl();
+    quote! {
+        #(#attrs)*
+        #vis enum #name #generics #where_clause {
+            #variants_quote
+        }
+        impl #display_generics ::core::fmt::Display for #name #ty_generics #display_where {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                match self {
+                    #display_quotes
+                }
+            }
+        }
+        impl #from_str_generics ::core::str::FromStr for #name #ty_generics #from_str_where {
+            type Err = ::indiscriminant::ParseError;
+            fn from_str(value: &str) -> Result<Self, Self::Err> {
+                match value {
+                    #exact_quotes
+                    _ => {}
+                }
+                #template_quotes
+                #fallback
+            }
+        }
+    }
+}
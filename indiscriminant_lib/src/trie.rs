@@ -0,0 +1,70 @@
+use proc_macro2::{Span, TokenStream};
+use quote::*;
+
+use syn::LitByteStr;
+
+/// Generates a byte trie over `bytes` that leaves the longest matching
+/// discriminant's variant and length in `result`
+pub(crate) fn generate_prefix_match(entries: &[(Vec<u8>, TokenStream)]) -> TokenStream {
+    let mut entries = entries.to_vec();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    generate_node(&entries, 0)
+}
+
+fn generate_node(entries: &[(Vec<u8>, TokenStream)], depth: usize) -> TokenStream {
+    let mut code = quote!();
+    let mut entries = entries;
+
+    // Sorting puts a discriminant ending at this node first, any longer match
+    // found further down overwrites it
+    if let Some((bytes, variant)) = entries.first() {
+        if bytes.len() == depth {
+            code.extend(quote! { result = Some((#variant, #depth)); });
+            entries = &entries[1..];
+        }
+    }
+    if entries.is_empty() {
+        return code;
+    }
+
+    // Compare runs of bytes shared by every remaining discriminant at once
+    let (first, last) = (&entries[0].0, &entries[entries.len() - 1].0);
+    let common = depth
+        + first[depth..]
+            .iter()
+            .zip(&last[depth..])
+            .take_while(|(a, b)| a == b)
+            .count();
+    if common > depth + 1 {
+        let prefix = LitByteStr::new(&first[depth..common], Span::call_site());
+        let child = generate_node(entries, common);
+        code.extend(quote! {
+            if bytes.get(#depth..#common) == Some(&#prefix[..]) {
+                #child
+            }
+        });
+        return code;
+    }
+
+    // Otherwise branch on the next byte
+    let mut arms = quote!();
+    let mut start = 0;
+    while start < entries.len() {
+        let byte = entries[start].0[depth];
+        let end = start
+            + entries[start..]
+                .iter()
+                .take_while(|(bytes, _)| bytes[depth] == byte)
+                .count();
+        let child = generate_node(&entries[start..end], depth + 1);
+        arms.extend(quote! { Some(#byte) => { #child } });
+        start = end;
+    }
+    code.extend(quote! {
+        match bytes.get(#depth) {
+            #arms
+            _ => {}
+        }
+    });
+    code
+}
@@ -0,0 +1,89 @@
+use indiscriminant_lib::indiscriminant_bits::indiscriminant_bits;
+use indiscriminant_lib::indiscriminant_str::indiscriminant_str;
+use quote::*;
+
+#[test]
+#[should_panic(expected = "Glob patterns are not supported with template variants!")]
+fn test_template_glob() {
+    indiscriminant_str(
+        quote! {},
+        quote! {
+            enum Color {
+                #[glob("r*")]
+                Red = "red",
+                Gray(f32) = "gray({0})",
+            }
+        },
+    );
+}
+
+#[test]
+#[should_panic(expected = "C strings are not supported with template variants!")]
+fn test_template_cstr() {
+    indiscriminant_str(
+        quote! { cstr },
+        quote! {
+            enum Color {
+                Red = "red",
+                Gray(f32) = "gray({0})",
+            }
+        },
+    );
+}
+
+#[test]
+#[should_panic(expected = "Unknown encoding gray_code!")]
+fn test_unknown_encoding() {
+    indiscriminant_bits(
+        quote! { u8, 4, encoding = gray_code },
+        quote! {
+            enum State {
+                Idle = 0,
+                Busy = 1,
+            }
+        },
+    );
+}
+
+#[test]
+#[should_panic(expected = "Encodings not supported with nested variants!")]
+fn test_nested_encoding() {
+    indiscriminant_bits(
+        quote! { u8, 6, encoding = gray },
+        quote! {
+            enum Opcode {
+                Nop = 0,
+                #[nested]
+                Alu(AluOp) = 1,
+            }
+        },
+    );
+}
+
+#[test]
+#[should_panic(expected = "Encoding given more than once!")]
+fn test_repeated_encoding() {
+    indiscriminant_bits(
+        quote! { u8, 4, encoding = gray, encoding = bcd },
+        quote! {
+            enum State {
+                Idle = 0,
+                Busy = 1,
+            }
+        },
+    );
+}
+
+#[test]
+#[should_panic(expected = "Default given more than once!")]
+fn test_repeated_default() {
+    indiscriminant_bits(
+        quote! { u8, 4, Default, Default = 3 },
+        quote! {
+            enum State {
+                Idle = 0,
+                Busy = 1,
+            }
+        },
+    );
+}
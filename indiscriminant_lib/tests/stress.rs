@@ -0,0 +1,35 @@
+use indiscriminant_lib::indiscriminant_bits::indiscriminant_bits;
+use proc_macro2::{Literal, TokenStream};
+use quote::*;
+
+fn generate_enum(count: usize) -> TokenStream {
+    let variants = (0..count).map(|i| {
+        let variant = format_ident!("V{}", i);
+        let discriminant = Literal::usize_unsuffixed(i);
+        quote! { #variant = #discriminant, }
+    });
+    quote! {
+        pub enum Huge {
+            #(#variants)*
+        }
+    }
+}
+
+/// Expands an enum of `count` variants, checking the output stays within a
+/// fixed size per variant
+fn expand(args: TokenStream, count: usize) -> String {
+    let expanded = indiscriminant_bits(args, generate_enum(count)).to_string();
+    assert!(expanded.len() < 512 * count, "Expansion too large!");
+    expanded
+}
+
+#[test]
+fn test_huge_enum() {
+    // Covering the whole u16 space
+    let expanded = expand(quote! { u16 }, 1 << 16);
+    assert!(expanded.contains("V65535 = 65535usize"));
+
+    // Leaving only the last value for an automatic default
+    let expanded = expand(quote! { u16, Default }, (1 << 16) - 1);
+    assert!(expanded.contains("Default = 65535usize"));
+}
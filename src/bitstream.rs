@@ -0,0 +1,173 @@
+use core::fmt;
+
+use crate::packed::{from_raw, read_bits, to_raw, write_bits};
+use crate::Bits;
+
+/// The order bits are read from or written to a byte stream in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BitOrder {
+    /// Bits fill each byte from its most-significant bit down, with each
+    /// value's most-significant bit first, as in most network headers
+    MsbFirst,
+    /// Bits fill each byte from its least-significant bit up, with each
+    /// value's least-significant bit first, as in `PackedVec`
+    LsbFirst,
+}
+
+/// The error returned when a `BitReader` cannot read a value
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReadError {
+    /// Fewer bits remain than the value needs
+    Truncated,
+    /// The bits read are not a discriminant of the enum
+    InvalidValue(u128),
+    /// A varint uses more bytes than its value needs
+    Overlong,
+    /// A varint's value does not fit in the backing integer
+    Overflow,
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Truncated => f.write_str("input ends before the value"),
+            Self::InvalidValue(value) => write!(f, "value {} matches no variant", value),
+            Self::Overlong => f.write_str("varint has an overlong encoding"),
+            Self::Overflow => f.write_str("varint overflows the backing integer"),
+        }
+    }
+}
+
+impl std::error::Error for ReadError {}
+
+fn read_bits_msb(bytes: &[u8], start: usize, width: u8) -> u128 {
+    let (mut value, mut done) = (0u128, 0);
+    while done < width as usize {
+        let bit = start + done;
+        let offset = bit % 8;
+        let take = (8 - offset).min(width as usize - done);
+        let chunk = (bytes[bit / 8] >> (8 - offset - take)) as u128 & ((1 << take) - 1);
+        value = value.checked_shl(take as u32).unwrap_or(0) | chunk;
+        done += take;
+    }
+    value
+}
+
+fn write_bits_msb(bytes: &mut [u8], start: usize, width: u8, value: u128) {
+    let mut done = 0;
+    while done < width as usize {
+        let bit = start + done;
+        let offset = bit % 8;
+        let take = (8 - offset).min(width as usize - done);
+        let shift = 8 - offset - take;
+        let mask = (((1u16 << take) - 1) << shift) as u8;
+        let chunk = ((value >> (width as usize - done - take)) as u8) << shift;
+        bytes[bit / 8] = (bytes[bit / 8] & !mask) | (chunk & mask);
+        done += take;
+    }
+}
+
+/// Reads `indiscriminant_bits` enums and plain integers of any bit-width
+/// from a byte slice
+#[derive(Clone, Debug)]
+pub struct BitReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    order: BitOrder,
+}
+
+impl<'a> BitReader<'a> {
+    pub fn new(bytes: &'a [u8], order: BitOrder) -> Self {
+        Self {
+            bytes,
+            position: 0,
+            order,
+        }
+    }
+
+    /// How many bits have been read so far
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// How many bits are left to read
+    pub fn remaining(&self) -> usize {
+        self.bytes.len() * 8 - self.position
+    }
+
+    /// Reads the next `width` bits as an integer, leaving the position
+    /// unchanged on error
+    pub fn read_bits(&mut self, width: u8) -> Result<u128, ReadError> {
+        assert!(width <= 128, "Bit-width {} too large!", width);
+        if (width as usize) > self.remaining() {
+            return Err(ReadError::Truncated);
+        }
+        let value = match self.order {
+            BitOrder::MsbFirst => read_bits_msb(self.bytes, self.position, width),
+            BitOrder::LsbFirst => read_bits(self.bytes, self.position, width),
+        };
+        self.position += width as usize;
+        Ok(value)
+    }
+
+    /// Reads the next `BIT_WIDTH` bits as an enum, leaving the position
+    /// unchanged on error
+    pub fn read<E: Bits>(&mut self) -> Result<E, ReadError> {
+        let position = self.position;
+        let raw = self.read_bits(E::BIT_WIDTH)?;
+        from_raw(raw).ok_or_else(|| {
+            self.position = position;
+            ReadError::InvalidValue(raw)
+        })
+    }
+}
+
+/// Writes `indiscriminant_bits` enums and plain integers of any bit-width
+/// to a growing byte vector
+#[derive(Clone, Debug)]
+pub struct BitWriter {
+    bytes: Vec<u8>,
+    position: usize,
+    order: BitOrder,
+}
+
+impl BitWriter {
+    pub fn new(order: BitOrder) -> Self {
+        Self {
+            bytes: Vec::new(),
+            position: 0,
+            order,
+        }
+    }
+
+    /// How many bits have been written so far
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Writes the low `width` bits of `value`
+    pub fn write_bits(&mut self, value: u128, width: u8) {
+        assert!(width <= 128, "Bit-width {} too large!", width);
+        self.bytes
+            .resize((self.position + width as usize).div_ceil(8), 0);
+        match self.order {
+            BitOrder::MsbFirst => write_bits_msb(&mut self.bytes, self.position, width, value),
+            BitOrder::LsbFirst => write_bits(&mut self.bytes, self.position, width, value),
+        }
+        self.position += width as usize;
+    }
+
+    /// Writes an enum in `BIT_WIDTH` bits
+    pub fn write<E: Bits>(&mut self, value: &E) {
+        self.write_bits(to_raw(value), E::BIT_WIDTH);
+    }
+
+    /// The bytes written so far, with any unwritten bits of the last byte zeroed
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
@@ -0,0 +1,30 @@
+/// The order of the bytes making up a multi-byte integer
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ByteOrder {
+    BigEndian,
+    LittleEndian,
+}
+
+/// Assembles an integer from all of `bytes`, which holds at most 16
+pub(crate) fn from_bytes(bytes: &[u8], order: ByteOrder) -> u128 {
+    let mut value = [0; 16];
+    match order {
+        ByteOrder::BigEndian => {
+            value[16 - bytes.len()..].copy_from_slice(bytes);
+            u128::from_be_bytes(value)
+        }
+        ByteOrder::LittleEndian => {
+            value[..bytes.len()].copy_from_slice(bytes);
+            u128::from_le_bytes(value)
+        }
+    }
+}
+
+/// Fills all of `bytes` with the low bytes of `value`
+pub(crate) fn to_bytes(value: u128, bytes: &mut [u8], order: ByteOrder) {
+    let size = bytes.len();
+    match order {
+        ByteOrder::BigEndian => bytes.copy_from_slice(&value.to_be_bytes()[16 - size..]),
+        ByteOrder::LittleEndian => bytes.copy_from_slice(&value.to_le_bytes()[..size]),
+    }
+}
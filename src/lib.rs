@@ -1 +1,209 @@
 pub use indiscriminant_impl::*;
+
+mod bitstream;
+mod byte_order;
+mod packed;
+mod varint;
+
+pub use bitstream::{BitOrder, BitReader, BitWriter, ReadError};
+pub use byte_order::ByteOrder;
+pub use packed::{PackedIter, PackedSlice, PackedVec};
+
+/// Conversions shared by every `indiscriminant_bits` enum, so they can be
+/// used generically
+pub trait Bits: Sized {
+    /// The backing integer type
+    type Int: Copy + Into<u128> + TryFrom<u128>;
+    /// How many least-significant bits of the backing integer the enum uses
+    const BIT_WIDTH: u8;
+
+    fn to_int(&self) -> Self::Int;
+
+    /// Converts from an integer, returning `None` where `from_int` has no
+    /// variant for the value
+    fn try_from_int(value: Self::Int) -> Option<Self>;
+
+    /// Reads the backing integer from the front of `buf` and advances past it
+    fn get(buf: &mut &[u8], order: ByteOrder) -> Result<Self, ReadError> {
+        let size = core::mem::size_of::<Self::Int>();
+        if buf.len() < size {
+            return Err(ReadError::Truncated);
+        }
+        let raw = byte_order::from_bytes(&buf[..size], order);
+        let value = packed::from_raw(raw).ok_or(ReadError::InvalidValue(raw))?;
+        *buf = &buf[size..];
+        Ok(value)
+    }
+
+    /// Appends the backing integer to `buf`
+    fn put(&self, buf: &mut Vec<u8>, order: ByteOrder) {
+        let size = core::mem::size_of::<Self::Int>();
+        let start = buf.len();
+        buf.resize(start + size, 0);
+        byte_order::to_bytes(self.to_int().into(), &mut buf[start..], order);
+    }
+
+    /// How many bytes `encode_varint` writes for this value
+    fn encoded_len(&self) -> usize {
+        varint::encoded_len(self.to_int().into())
+    }
+
+    /// Writes the backing integer as unsigned LEB128, returning the number of
+    /// bytes written and panicking if `buf` is shorter than `encoded_len`
+    fn encode_varint(&self, buf: &mut [u8]) -> usize {
+        varint::encode(self.to_int().into(), buf)
+    }
+
+    /// Reads an unsigned LEB128 backing integer from the front of `buf`,
+    /// returning the value with the number of bytes read. Encodings longer
+    /// than needed are rejected, and values are converted as by `try_from_int`
+    fn decode_varint(buf: &[u8]) -> Result<(Self, usize), ReadError> {
+        let (raw, len) = varint::decode(buf)?;
+        let int = Self::Int::try_from(raw).map_err(|_| ReadError::Overflow)?;
+        let value = Self::try_from_int(int).ok_or(ReadError::InvalidValue(raw))?;
+        Ok((value, len))
+    }
+
+    /// How many bytes `encode_signed_varint` writes for this value
+    fn encoded_signed_len(&self) -> usize {
+        varint::signed_encoded_len(varint::sign_extend(self.to_int().into(), Self::BIT_WIDTH))
+    }
+
+    /// Writes the low `BIT_WIDTH` bits of the backing integer as signed
+    /// LEB128, reading them as two's complement like WebAssembly value types.
+    /// Returns the number of bytes written and panics if `buf` is shorter than
+    /// `encoded_signed_len`
+    fn encode_signed_varint(&self, buf: &mut [u8]) -> usize {
+        let value = varint::sign_extend(self.to_int().into(), Self::BIT_WIDTH);
+        varint::encode_signed(value, buf)
+    }
+
+    /// Reads a signed LEB128 value from the front of `buf` as the two's
+    /// complement of the low `BIT_WIDTH` bits, returning it with the number of
+    /// bytes read. Encodings longer than needed and values outside the
+    /// bit-width are rejected, and values are converted as by `try_from_int`
+    fn decode_signed_varint(buf: &[u8]) -> Result<(Self, usize), ReadError> {
+        let (value, len) = varint::decode_signed(buf)?;
+        let raw = varint::truncate_signed(value, Self::BIT_WIDTH).ok_or(ReadError::Overflow)?;
+        let int = Self::Int::try_from(raw).map_err(|_| ReadError::Overflow)?;
+        let value = Self::try_from_int(int).ok_or(ReadError::InvalidValue(raw))?;
+        Ok((value, len))
+    }
+
+    /// Reads the backing integer from `reader`, failing with
+    /// `ErrorKind::InvalidData` when it matches no variant
+    #[cfg(feature = "std")]
+    fn read_from<R: std::io::Read>(reader: &mut R, order: ByteOrder) -> std::io::Result<Self> {
+        let mut bytes = [0; 16];
+        let bytes = &mut bytes[..core::mem::size_of::<Self::Int>()];
+        reader.read_exact(bytes)?;
+        let raw = byte_order::from_bytes(bytes, order);
+        packed::from_raw(raw).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                ReadError::InvalidValue(raw),
+            )
+        })
+    }
+
+    /// Writes the backing integer to `writer`
+    #[cfg(feature = "std")]
+    fn write_to<W: std::io::Write>(&self, writer: &mut W, order: ByteOrder) -> std::io::Result<()> {
+        let mut bytes = [0; 16];
+        let bytes = &mut bytes[..core::mem::size_of::<Self::Int>()];
+        byte_order::to_bytes(self.to_int().into(), bytes, order);
+        writer.write_all(bytes)
+    }
+}
+
+/// The result of feeding input to a byte string enum's recognizer
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Recognized<T> {
+    /// The input so far is a discriminant that no more input can extend
+    Matched(T),
+    /// The input so far is the start of at least one longer discriminant
+    NeedMore,
+    /// No discriminant starts with the input so far
+    NoMatch,
+}
+
+/// The error returned when a string matches none of an enum's variants
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParseError;
+
+impl core::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("string matches no variant")
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[doc(hidden)]
+pub mod __private {
+    /// Compares two byte slices, usable in const context
+    pub const fn bytes_eq(a: &[u8], b: &[u8]) -> bool {
+        if a.len() != b.len() {
+            return false;
+        }
+        let mut i = 0;
+        while i < a.len() {
+            if a[i] != b[i] {
+                return false;
+            }
+            i += 1;
+        }
+        true
+    }
+
+    /// Matches `value` against a pattern where `*` matches any sequence of
+    /// characters and `?` matches any one character
+    pub const fn glob_match(pattern: &str, value: &str) -> bool {
+        let (pattern, bytes) = (pattern.as_bytes(), value.as_bytes());
+        let (mut p, mut v) = (0, 0);
+        let mut star = None;
+        while v < bytes.len() {
+            if p < pattern.len() && pattern[p] == b'*' {
+                star = Some((p, v));
+                p += 1;
+            } else if p < pattern.len() && pattern[p] == b'?' {
+                p += 1;
+                v += char_len(bytes[v]);
+            } else if p < pattern.len() && pattern[p] == bytes[v] {
+                p += 1;
+                v += 1;
+            } else if let Some((star_p, star_v)) = star {
+                let next = star_v + char_len(bytes[star_v]);
+                star = Some((star_p, next));
+                p = star_p + 1;
+                v = next;
+            } else {
+                return false;
+            }
+        }
+        while p < pattern.len() {
+            if pattern[p] != b'*' {
+                return false;
+            }
+            p += 1;
+        }
+        true
+    }
+
+    /// Decodes UTF-16 for matching against glob patterns, `None` if invalid
+    pub fn decode_utf16(value: &[u16]) -> Option<String> {
+        char::decode_utf16(value.iter().copied())
+            .collect::<Result<_, _>>()
+            .ok()
+    }
+
+    /// The length of the UTF-8 character starting with `byte`
+    const fn char_len(byte: u8) -> usize {
+        match byte {
+            0x00..=0x7f => 1,
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            _ => 4,
+        }
+    }
+}
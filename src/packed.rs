@@ -0,0 +1,331 @@
+use core::fmt;
+use core::marker::PhantomData;
+
+use crate::Bits;
+
+/// Reads `width` bits starting at bit `start`, where bits are numbered from
+/// the least-significant bit of the first byte
+pub(crate) fn read_bits(bytes: &[u8], start: usize, width: u8) -> u128 {
+    let (mut value, mut done) = (0u128, 0);
+    while done < width as usize {
+        let bit = start + done;
+        let shift = bit % 8;
+        let take = (8 - shift).min(width as usize - done);
+        let chunk = (bytes[bit / 8] >> shift) as u128 & ((1 << take) - 1);
+        value |= chunk << done;
+        done += take;
+    }
+    value
+}
+
+/// Writes the low `width` bits of `value` starting at bit `start`
+pub(crate) fn write_bits(bytes: &mut [u8], start: usize, width: u8, value: u128) {
+    let mut done = 0;
+    while done < width as usize {
+        let bit = start + done;
+        let shift = bit % 8;
+        let take = (8 - shift).min(width as usize - done);
+        let mask = (((1u16 << take) - 1) << shift) as u8;
+        let chunk = ((value >> done) as u8) << shift;
+        bytes[bit / 8] = (bytes[bit / 8] & !mask) | (chunk & mask);
+        done += take;
+    }
+}
+
+fn byte_len<E: Bits>(len: usize) -> usize {
+    (len * E::BIT_WIDTH as usize).div_ceil(8)
+}
+
+pub(crate) fn to_raw<E: Bits>(value: &E) -> u128 {
+    value.to_int().into()
+        & u128::MAX
+            .checked_shr(128 - E::BIT_WIDTH as u32)
+            .unwrap_or(0)
+}
+
+pub(crate) fn from_raw<E: Bits>(raw: u128) -> Option<E> {
+    E::Int::try_from(raw).ok().and_then(E::try_from_int)
+}
+
+/// Checks `bytes` holds exactly `len` elements that all decode to a variant,
+/// with the unused bits of the last byte zeroed
+fn is_valid<E: Bits>(bytes: &[u8], len: usize) -> bool {
+    if bytes.len() != byte_len::<E>(len) {
+        return false;
+    }
+    let used = len * E::BIT_WIDTH as usize;
+    if used % 8 != 0 && bytes[bytes.len() - 1] >> (used % 8) != 0 {
+        return false;
+    }
+    (0..len)
+        .all(|i| from_raw::<E>(read_bits(bytes, i * E::BIT_WIDTH as usize, E::BIT_WIDTH)).is_some())
+}
+
+/// A growable list of `indiscriminant_bits` enums, each stored in exactly
+/// `BIT_WIDTH` bits
+///
+/// Elements are packed from the least-significant bit of the first byte
+/// upwards, with any unused bits of the last byte zeroed
+pub struct PackedVec<E> {
+    bytes: Vec<u8>,
+    len: usize,
+    marker: PhantomData<E>,
+}
+
+impl<E: Bits> PackedVec<E> {
+    pub fn new() -> Self {
+        Self {
+            bytes: Vec::new(),
+            len: 0,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            bytes: Vec::with_capacity(byte_len::<E>(capacity)),
+            len: 0,
+            marker: PhantomData,
+        }
+    }
+
+    /// Takes packed bytes holding `len` elements, returning `None` if the
+    /// byte count does not match, an element is not a variant or an unused
+    /// bit of the last byte is set
+    pub fn from_bytes(bytes: Vec<u8>, len: usize) -> Option<Self> {
+        is_valid::<E>(&bytes, len).then_some(Self {
+            bytes,
+            len,
+            marker: PhantomData,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn push(&mut self, value: E) {
+        self.bytes.resize(byte_len::<E>(self.len + 1), 0);
+        write_bits(
+            &mut self.bytes,
+            self.len * E::BIT_WIDTH as usize,
+            E::BIT_WIDTH,
+            to_raw(&value),
+        );
+        self.len += 1;
+    }
+
+    pub fn pop(&mut self) -> Option<E> {
+        let value = self.get(self.len.checked_sub(1)?)?;
+        self.len -= 1;
+        let start = self.len * E::BIT_WIDTH as usize;
+        write_bits(&mut self.bytes, start, E::BIT_WIDTH, 0);
+        self.bytes.truncate(byte_len::<E>(self.len));
+        Some(value)
+    }
+
+    pub fn get(&self, index: usize) -> Option<E> {
+        self.as_slice().get(index)
+    }
+
+    /// Replaces the element at `index`, panicking if it is out of bounds
+    pub fn set(&mut self, index: usize, value: E) {
+        assert!(index < self.len, "Index {} out of bounds!", index);
+        write_bits(
+            &mut self.bytes,
+            index * E::BIT_WIDTH as usize,
+            E::BIT_WIDTH,
+            to_raw(&value),
+        );
+    }
+
+    pub fn clear(&mut self) {
+        self.bytes.clear();
+        self.len = 0;
+    }
+
+    pub fn iter(&self) -> PackedIter<'_, E> {
+        self.as_slice().iter()
+    }
+
+    pub fn as_slice(&self) -> PackedSlice<'_, E> {
+        PackedSlice {
+            bytes: &self.bytes,
+            len: self.len,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+impl<E: Bits> Default for PackedVec<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E> Clone for PackedVec<E> {
+    fn clone(&self) -> Self {
+        Self {
+            bytes: self.bytes.clone(),
+            len: self.len,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<E> PartialEq for PackedVec<E> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.bytes == other.bytes
+    }
+}
+
+impl<E> Eq for PackedVec<E> {}
+
+impl<E: Bits + fmt::Debug> fmt::Debug for PackedVec<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<E: Bits> Extend<E> for PackedVec<E> {
+    fn extend<I: IntoIterator<Item = E>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<E: Bits> FromIterator<E> for PackedVec<E> {
+    fn from_iter<I: IntoIterator<Item = E>>(iter: I) -> Self {
+        let mut result = Self::new();
+        result.extend(iter);
+        result
+    }
+}
+
+impl<'a, E: Bits> IntoIterator for &'a PackedVec<E> {
+    type Item = E;
+    type IntoIter = PackedIter<'a, E>;
+
+    fn into_iter(self) -> PackedIter<'a, E> {
+        self.iter()
+    }
+}
+
+/// A borrowed view of packed `indiscriminant_bits` enums, laid out as in
+/// [`PackedVec`]
+pub struct PackedSlice<'a, E> {
+    bytes: &'a [u8],
+    len: usize,
+    marker: PhantomData<E>,
+}
+
+impl<'a, E: Bits> PackedSlice<'a, E> {
+    /// Views packed bytes holding `len` elements, with the same checks as
+    /// [`PackedVec::from_bytes`]
+    pub fn from_bytes(bytes: &'a [u8], len: usize) -> Option<Self> {
+        is_valid::<E>(bytes, len).then_some(Self {
+            bytes,
+            len,
+            marker: PhantomData,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<E> {
+        if index >= self.len {
+            return None;
+        }
+        let raw = read_bits(self.bytes, index * E::BIT_WIDTH as usize, E::BIT_WIDTH);
+        // Elements were checked to be variants when pushed or validated
+        Some(from_raw(raw).expect("Packed element is not a variant!"))
+    }
+
+    pub fn iter(&self) -> PackedIter<'a, E> {
+        PackedIter {
+            slice: PackedSlice {
+                bytes: self.bytes,
+                len: self.len,
+                marker: PhantomData,
+            },
+            index: 0,
+        }
+    }
+
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    pub fn to_vec(&self) -> PackedVec<E> {
+        PackedVec {
+            bytes: self.bytes.to_vec(),
+            len: self.len,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<E> Clone for PackedSlice<'_, E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<E> Copy for PackedSlice<'_, E> {}
+
+impl<E: Bits + fmt::Debug> fmt::Debug for PackedSlice<'_, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, E: Bits> IntoIterator for PackedSlice<'a, E> {
+    type Item = E;
+    type IntoIter = PackedIter<'a, E>;
+
+    fn into_iter(self) -> PackedIter<'a, E> {
+        self.iter()
+    }
+}
+
+/// An iterator over the elements of a [`PackedVec`] or [`PackedSlice`]
+pub struct PackedIter<'a, E> {
+    slice: PackedSlice<'a, E>,
+    index: usize,
+}
+
+impl<E: Bits> Iterator for PackedIter<'_, E> {
+    type Item = E;
+
+    fn next(&mut self) -> Option<E> {
+        let value = self.slice.get(self.index)?;
+        self.index += 1;
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.slice.len - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<E: Bits> ExactSizeIterator for PackedIter<'_, E> {}
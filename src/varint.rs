@@ -0,0 +1,108 @@
+use crate::ReadError;
+
+/// How many bytes the unsigned LEB128 encoding of `value` takes
+pub(crate) fn encoded_len(value: u128) -> usize {
+    ((128 - value.leading_zeros() as usize).max(1)).div_ceil(7)
+}
+
+/// Writes `value` as unsigned LEB128, returning the number of bytes written
+pub(crate) fn encode(mut value: u128, buf: &mut [u8]) -> usize {
+    let len = encoded_len(value);
+    assert!(buf.len() >= len, "Buffer too small for varint!");
+    for byte in &mut buf[..len - 1] {
+        *byte = value as u8 | 0x80;
+        value >>= 7;
+    }
+    buf[len - 1] = value as u8;
+    len
+}
+
+/// Reads an unsigned LEB128 value from the front of `buf`, returning it with
+/// the number of bytes read
+pub(crate) fn decode(buf: &[u8]) -> Result<(u128, usize), ReadError> {
+    let mut value = 0u128;
+    for (i, byte) in buf.iter().enumerate() {
+        let bits = (*byte & 0x7f) as u128;
+        let shift = 7 * i as u32;
+        if bits != 0 {
+            if shift >= 128 || (bits << shift) >> shift != bits {
+                return Err(ReadError::Overflow);
+            }
+            value |= bits << shift;
+        }
+        if byte & 0x80 == 0 {
+            // A final zero group adds nothing, so a shorter encoding exists
+            if i > 0 && *byte == 0 {
+                return Err(ReadError::Overlong);
+            }
+            return Ok((value, i + 1));
+        }
+    }
+    Err(ReadError::Truncated)
+}
+
+/// Reads the low `width` bits of `raw` as a two's complement integer
+pub(crate) fn sign_extend(raw: u128, width: u8) -> i128 {
+    if width == 0 {
+        return 0;
+    }
+    let shift = 128 - width as u32;
+    ((raw << shift) as i128) >> shift
+}
+
+/// Converts `value` to its low `width` bits, `None` if it needs more
+pub(crate) fn truncate_signed(value: i128, width: u8) -> Option<u128> {
+    let raw = value as u128 & u128::MAX.checked_shr(128 - width as u32).unwrap_or(0);
+    (sign_extend(raw, width) == value).then_some(raw)
+}
+
+/// How many bytes the signed LEB128 encoding of `value` takes
+pub(crate) fn signed_encoded_len(value: i128) -> usize {
+    // One more bit than the value's significant bits holds its sign
+    (129 - (value ^ (value >> 127)).leading_zeros() as usize).div_ceil(7)
+}
+
+/// Writes `value` as signed LEB128, returning the number of bytes written
+pub(crate) fn encode_signed(mut value: i128, buf: &mut [u8]) -> usize {
+    let len = signed_encoded_len(value);
+    assert!(buf.len() >= len, "Buffer too small for varint!");
+    for byte in &mut buf[..len - 1] {
+        *byte = value as u8 | 0x80;
+        value >>= 7;
+    }
+    buf[len - 1] = value as u8 & 0x7f;
+    len
+}
+
+/// Reads a signed LEB128 value from the front of `buf`, returning it with the
+/// number of bytes read
+pub(crate) fn decode_signed(buf: &[u8]) -> Result<(i128, usize), ReadError> {
+    let len = match buf.iter().position(|byte| byte & 0x80 == 0) {
+        Some(last) => last + 1,
+        None => return Err(ReadError::Truncated),
+    };
+    let last = buf[len - 1];
+
+    // A final group only repeating the sign of the one before adds nothing
+    if len > 1 {
+        let negative = buf[len - 2] & 0x40 != 0;
+        if (last == 0 && !negative) || (last == 0x7f && negative) {
+            return Err(ReadError::Overlong);
+        }
+    }
+
+    // The 19th group holds the sign bit, and any bits above it must match
+    let max_len = 128usize.div_ceil(7);
+    if len > max_len || (len == max_len && !matches!(last >> 1, 0 | 0x3f)) {
+        return Err(ReadError::Overflow);
+    }
+    let mut value = 0i128;
+    for (i, byte) in buf[..len].iter().enumerate() {
+        value |= ((byte & 0x7f) as i128) << (7 * i);
+    }
+    let end = 7 * len as u32;
+    if end < 128 && last & 0x40 != 0 {
+        value |= -1 << end;
+    }
+    Ok((value, len))
+}
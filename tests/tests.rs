@@ -1,3 +1,6 @@
+// The baseline tests compare against None directly
+#![allow(clippy::partialeq_to_none)]
+
 use indiscriminant::*;
 
 #[test]
@@ -455,3 +458,1238 @@ fn test_all() {
         B = 1,
     }
 }
+
+#[test]
+fn test_str_rename_all() {
+    #[indiscriminant_str(rename_all = "snake_case")]
+    #[derive(PartialEq)]
+    pub enum TestEnumSnake {
+        HttpRequest,
+        Ok,
+        Other = "something-else",
+    }
+
+    assert!(TestEnumSnake::HttpRequest.to_str() == "http_request");
+    assert!(TestEnumSnake::Ok.to_str() == "ok");
+    assert!(TestEnumSnake::Other.to_str() == "something-else");
+
+    assert!(TestEnumSnake::from_str("http_request") == Some(TestEnumSnake::HttpRequest));
+    assert!(TestEnumSnake::from_str("something-else") == Some(TestEnumSnake::Other));
+    assert!(TestEnumSnake::from_str("other").is_none());
+
+    #[indiscriminant_str(rename_all = "kebab-case", Default = "unknown")]
+    #[derive(PartialEq)]
+    pub enum TestEnumKebab {
+        ContentType,
+        ContentLength,
+    }
+
+    assert!(TestEnumKebab::ContentType.to_str() == "content-type");
+    assert!(TestEnumKebab::from_str("content-length") == TestEnumKebab::ContentLength);
+    assert!(TestEnumKebab::from_str("ContentLength") == TestEnumKebab::Default);
+
+    #[indiscriminant_str(rename_all = "SCREAMING_SNAKE_CASE")]
+    pub enum TestEnumScreaming {
+        MaxValue,
+    }
+
+    assert!(TestEnumScreaming::MaxValue.to_str() == "MAX_VALUE");
+
+    #[indiscriminant_str(rename_all = "lowercase")]
+    pub enum TestEnumLower {
+        MaxValue,
+    }
+
+    assert!(TestEnumLower::MaxValue.to_str() == "maxvalue");
+
+    #[indiscriminant(rename_all = "camelCase")]
+    pub enum TestEnumCamel {
+        MaxValue,
+    }
+
+    assert!(TestEnumCamel::MaxValue.to_str() == "maxValue");
+}
+
+#[test]
+fn test_parse_prefix() {
+    #[indiscriminant_str()]
+    #[derive(PartialEq)]
+    pub enum Operator {
+        Lt = "<",
+        Le = "<=",
+        Shl = "<<",
+        ShlAssign = "<<=",
+        Eq = "==",
+        Assign = "=",
+        Return = "return",
+        Ref = "ref",
+    }
+
+    assert!(Operator::parse_prefix("<") == Some((Operator::Lt, "")));
+    assert!(Operator::parse_prefix("<= b") == Some((Operator::Le, " b")));
+    assert!(Operator::parse_prefix("<<=1") == Some((Operator::ShlAssign, "1")));
+    assert!(Operator::parse_prefix("<<1") == Some((Operator::Shl, "1")));
+    assert!(Operator::parse_prefix("===") == Some((Operator::Eq, "=")));
+    assert!(Operator::parse_prefix("return;") == Some((Operator::Return, ";")));
+    assert!(Operator::parse_prefix("retur").is_none());
+    assert!(Operator::parse_prefix("refs") == Some((Operator::Ref, "s")));
+    assert!(Operator::parse_prefix("").is_none());
+    assert!(Operator::parse_prefix("> b").is_none());
+
+    #[indiscriminant_str(Default = "?")]
+    #[derive(PartialEq)]
+    pub enum Keyword {
+        Empty = "",
+        Let = "let",
+        Const = "const",
+    }
+
+    assert!(Keyword::parse_prefix("let x") == Some((Keyword::Let, " x")));
+    assert!(Keyword::parse_prefix("?") == Some((Keyword::Empty, "?")));
+
+    #[indiscriminant_byte_str()]
+    #[derive(PartialEq)]
+    pub enum Method {
+        Get = b"GET",
+        Post = b"POST",
+        Put = b"PUT",
+        Patch = b"PATCH",
+    }
+
+    assert!(Method::parse_prefix(b"GET / HTTP/1.1") == Some((Method::Get, &b" / HTTP/1.1"[..])));
+    assert!(Method::parse_prefix(b"PATCH") == Some((Method::Patch, &b""[..])));
+    assert!(Method::parse_prefix(b"PU").is_none());
+
+    #[indiscriminant_byte_str(Default = b"")]
+    #[derive(PartialEq)]
+    pub enum Marker {
+        Start = b"\xff\xd8",
+        End = b"\xff\xd9",
+    }
+
+    assert!(Marker::parse_prefix(b"\xff\xd9\x00") == Some((Marker::End, &b"\x00"[..])));
+    assert!(Marker::parse_prefix(b"\xff").is_none());
+}
+
+#[test]
+fn test_sniff() {
+    #[indiscriminant_byte_str()]
+    #[derive(PartialEq)]
+    pub enum FileFormat {
+        Png = b"\x89PNG\r\n\x1a\n",
+        #[sniff(signature = b"ftypisom", offset = 4)]
+        Mp4 = b"mp4",
+        Zip = b"PK\x03\x04",
+        #[sniff(signature = b"PK")]
+        ZipLike = b"pk",
+        #[sniff(signature = b"GIF8\x00a", mask = b"\xff\xff\xff\xff\x00\xff")]
+        Gif = b"GIF",
+    }
+
+    assert!(FileFormat::sniff(b"\x89PNG\r\n\x1a\n\x00\x00") == Some(FileFormat::Png));
+    assert!(FileFormat::sniff(b"\x00\x00\x00\x20ftypisom") == Some(FileFormat::Mp4));
+    assert!(FileFormat::sniff(b"PK\x03\x04\x14\x00") == Some(FileFormat::Zip));
+    assert!(FileFormat::sniff(b"PK\x05\x06") == Some(FileFormat::ZipLike));
+    assert!(FileFormat::sniff(b"GIF89a") == Some(FileFormat::Gif));
+    assert!(FileFormat::sniff(b"GIF87a") == Some(FileFormat::Gif));
+    assert!(FileFormat::sniff(b"GIF8").is_none());
+    assert!(FileFormat::sniff(b"ftyp").is_none());
+    assert!(FileFormat::sniff(b"").is_none());
+
+    #[indiscriminant_byte_str(Default = b"")]
+    #[derive(PartialEq)]
+    pub enum Frame {
+        #[sniff(mask = b"\xdf\xdf\xdf")]
+        Get = b"GET",
+        Put = b"PUT",
+    }
+
+    assert!(Frame::sniff(b"get /") == Some(Frame::Get));
+    assert!(Frame::sniff(b"PUT /") == Some(Frame::Put));
+    assert!(Frame::sniff(b"put /").is_none());
+}
+
+#[test]
+fn test_fixed_size() {
+    #[indiscriminant_byte_str(size = 4)]
+    #[derive(PartialEq)]
+    pub enum Chunk {
+        Ihdr = b"IHDR",
+        Idat = b"IDAT",
+        Iend = b"IEND",
+    }
+
+    assert!(Chunk::Ihdr.to_array() == *b"IHDR");
+    assert!(Chunk::from_array(*b"IDAT") == Some(Chunk::Idat));
+    assert!(Chunk::from_array(*b"tEXt").is_none());
+
+    assert!(Chunk::Iend.to_u32_be() == 0x49454e44);
+    assert!(Chunk::Iend.to_u32_le() == 0x444e4549);
+    assert!(Chunk::from_u32_be(0x49484452) == Some(Chunk::Ihdr));
+    assert!(Chunk::from_u32_le(0x52444849) == Some(Chunk::Ihdr));
+    assert!(Chunk::from_u32_be(0x52444849).is_none());
+
+    #[indiscriminant_byte_str(size = 2, Default = b"??")]
+    #[derive(PartialEq)]
+    pub enum Magic {
+        Elf = b"\x7fE",
+        Mz = b"MZ",
+    }
+
+    assert!(Magic::Default.to_array() == *b"??");
+    assert!(Magic::from_array(*b"MZ") == Magic::Mz);
+    assert!(Magic::from_array(*b"ZM") == Magic::Default);
+    assert!(Magic::from_u16_be(0x7f45) == Magic::Elf);
+    assert!(Magic::Mz.to_u16_le() == 0x5a4d);
+
+    #[indiscriminant_byte_str(size = 3)]
+    #[derive(PartialEq)]
+    pub enum Code {
+        Abc = b"abc",
+        Xyz = b"xyz",
+    }
+
+    assert!(Code::Xyz.to_array() == *b"xyz");
+    assert!(Code::from_array(*b"abc") == Some(Code::Abc));
+    assert!(Code::from_array(*b"abd").is_none());
+}
+
+#[test]
+fn test_recognizer() {
+    #[indiscriminant_byte_str()]
+    #[derive(PartialEq)]
+    pub enum Method {
+        Get = b"GET",
+        Post = b"POST",
+        Put = b"PUT",
+        Patch = b"PATCH",
+        Lt = b"<",
+        Le = b"<=",
+    }
+
+    let mut recognizer = MethodRecognizer::new();
+    assert!(recognizer.feed(b"P") == Recognized::NeedMore);
+    assert!(recognizer.feed(b"") == Recognized::NeedMore);
+    assert!(recognizer.feed(b"AT") == Recognized::NeedMore);
+    assert!(recognizer.finish().is_none());
+    assert!(recognizer.feed(b"CH") == Recognized::Matched(Method::Patch));
+    assert!(recognizer.finish() == Some(Method::Patch));
+    assert!(recognizer.feed(b"!") == Recognized::NoMatch);
+    assert!(recognizer.feed(b"GET") == Recognized::NoMatch);
+    assert!(recognizer.finish().is_none());
+
+    let mut recognizer = MethodRecognizer::new();
+    assert!(recognizer.feed(b"GE") == Recognized::NeedMore);
+    assert!(recognizer.feed(b"T") == Recognized::Matched(Method::Get));
+
+    // A discriminant that a longer one extends only resolves on finish
+    let mut recognizer = MethodRecognizer::new();
+    assert!(recognizer.feed(b"<") == Recognized::NeedMore);
+    assert!(recognizer.finish() == Some(Method::Lt));
+    assert!(recognizer.feed(b"=") == Recognized::Matched(Method::Le));
+
+    let mut recognizer = MethodRecognizer::default();
+    assert!(recognizer.feed(b"PA") == Recognized::NeedMore);
+    assert!(recognizer.feed(b"X") == Recognized::NoMatch);
+}
+
+#[test]
+fn test_cstr() {
+    #[indiscriminant_str(cstr)]
+    #[derive(PartialEq)]
+    pub enum Level {
+        Debug = "debug",
+        Info = "info",
+        Empty = "",
+    }
+
+    assert!(Level::Debug.to_cstr().to_bytes_with_nul() == b"debug\0");
+    assert!(Level::Empty.to_cstr().to_bytes_with_nul() == b"\0");
+    assert!(Level::Info.to_cstr().to_str() == Ok("info"));
+
+    assert!(Level::from_cstr(c"info") == Some(Level::Info));
+    assert!(Level::from_cstr(c"") == Some(Level::Empty));
+    assert!(Level::from_cstr(c"\xff").is_none());
+
+    #[indiscriminant_str(rename_all = "lowercase", cstr, Default = "unknown")]
+    #[derive(PartialEq)]
+    pub enum Mode {
+        Read,
+        Write,
+    }
+
+    assert!(Mode::Write.to_cstr().to_bytes() == b"write");
+    assert!(Mode::Default.to_cstr().to_bytes_with_nul() == b"unknown\0");
+    assert!(Mode::from_cstr(c"read") == Mode::Read);
+    assert!(Mode::from_cstr(c"other") == Mode::Default);
+}
+
+#[test]
+fn test_utf16() {
+    #[indiscriminant_str()]
+    #[derive(PartialEq)]
+    pub enum Resource {
+        Icon = "ICON",
+        Note = "𝄞 note",
+        Empty = "",
+    }
+
+    let icon: Vec<u16> = "ICON".encode_utf16().collect();
+    let note: Vec<u16> = "𝄞 note".encode_utf16().collect();
+    assert!(Resource::Icon.to_utf16() == icon.as_slice());
+    assert!(Resource::Note.to_utf16() == note.as_slice());
+    assert!(Resource::Empty.to_utf16().is_empty());
+
+    assert!(Resource::from_utf16(&icon) == Some(Resource::Icon));
+    assert!(Resource::from_utf16(&note) == Some(Resource::Note));
+    assert!(Resource::from_utf16(&[]) == Some(Resource::Empty));
+    assert!(Resource::from_utf16(&note[..1]).is_none());
+
+    #[indiscriminant_str(Default = "?")]
+    #[derive(PartialEq)]
+    pub enum ResourceDefault {
+        Icon = "ICON",
+    }
+
+    assert!(ResourceDefault::Default.to_utf16() == [0x3f]);
+    assert!(ResourceDefault::from_utf16(&icon) == ResourceDefault::Icon);
+    assert!(ResourceDefault::from_utf16(&note) == ResourceDefault::Default);
+}
+
+#[test]
+fn test_glob() {
+    #[indiscriminant_str()]
+    #[derive(PartialEq)]
+    pub enum MediaType {
+        Json = "application/json",
+        #[glob("text/*")]
+        Text = "text/plain",
+        #[glob("text/h?ml")]
+        Html = "text/html",
+        #[glob("image/*")]
+        #[glob("video/*")]
+        Media = "image/png",
+        #[glob("*")]
+        Other = "application/octet-stream",
+    }
+
+    assert!(MediaType::Text.to_str() == "text/plain");
+    assert!(MediaType::from_str("text/plain") == Some(MediaType::Text));
+    assert!(MediaType::from_str("text/csv") == Some(MediaType::Text));
+    assert!(MediaType::from_str("text/html") == Some(MediaType::Html));
+    assert!(MediaType::from_str("text/hxml") == Some(MediaType::Html));
+    assert!(MediaType::from_str("video/mp4") == Some(MediaType::Media));
+    assert!(MediaType::from_str("application/json") == Some(MediaType::Json));
+    assert!(MediaType::from_str("application/xml") == Some(MediaType::Other));
+    assert!(MediaType::from_str("") == Some(MediaType::Other));
+
+    #[indiscriminant(Default = "unknown")]
+    #[derive(PartialEq)]
+    pub enum Protocol {
+        #[glob("HTTP/1.*")]
+        Http1,
+        #[glob("HTTP/2*")]
+        Http2,
+        #[glob("*.ñ?")]
+        Spanish,
+    }
+
+    assert!(Protocol::Http1.to_str() == "HTTP/1.");
+    assert!(Protocol::Http2.to_str() == "HTTP/2");
+    assert!(Protocol::from_str("HTTP/1.1") == Protocol::Http1);
+    assert!(Protocol::from_str("HTTP/2.0") == Protocol::Http2);
+    assert!(Protocol::from_str("a.ñé") == Protocol::Spanish);
+    assert!(Protocol::from_str("a.ñ") == Protocol::Default);
+    assert!(Protocol::from_str("HTTP/3") == Protocol::Default);
+
+    // Other conversions fall back to the patterns the same way
+    let utf16: Vec<u16> = "HTTP/1.0".encode_utf16().collect();
+    assert!(Protocol::from_utf16(&utf16) == Protocol::Http1);
+    assert!(Protocol::from_utf16(&[0xd800]) == Protocol::Default);
+    let utf16: Vec<u16> = "text/csv".encode_utf16().collect();
+    assert!(MediaType::from_utf16(&utf16) == Some(MediaType::Text));
+    assert!(Protocol::parse_prefix("HTTP/2") == Some((Protocol::Http2, "")));
+    assert!(Protocol::parse_prefix("a.ñé!") == Some((Protocol::Spanish, "!")));
+    assert!(Protocol::parse_prefix("unknown").is_none());
+    assert!(MediaType::parse_prefix("application/json") == Some((MediaType::Json, "")));
+
+    #[indiscriminant_str(cstr)]
+    #[derive(PartialEq)]
+    pub enum Charset {
+        #[glob("utf-*")]
+        Utf = "utf-8",
+        Ascii = "ascii",
+    }
+
+    assert!(Charset::from_cstr(c"utf-16") == Some(Charset::Utf));
+    assert!(Charset::from_cstr(c"ascii") == Some(Charset::Ascii));
+    assert!(Charset::from_cstr(c"latin1").is_none());
+    assert!(Charset::from_cstr(c"utf-\xff").is_none());
+}
+
+#[test]
+fn test_template() {
+    #[indiscriminant_str()]
+    #[derive(PartialEq)]
+    pub enum Color {
+        Red = "red",
+        Rgb { r: u8, g: u8, b: u8 } = "rgb({r},{g},{b})",
+        Gray(f32) = "gray({0})",
+        Named { name: String } = "{{{name}}}",
+        Pair(i32, i32) = "{1}..{0}",
+    }
+
+    assert!(Color::Red.to_string() == "red");
+    assert!(Color::Rgb { r: 1, g: 2, b: 3 }.to_string() == "rgb(1,2,3)");
+    assert!(Color::Gray(0.5).to_string() == "gray(0.5)");
+    assert!(Color::Named { name: "x".into() }.to_string() == "{x}");
+    assert!(Color::Pair(1, 2).to_string() == "2..1");
+
+    assert!("red".parse::<Color>() == Ok(Color::Red));
+    assert!("rgb(1,2,3)".parse::<Color>() == Ok(Color::Rgb { r: 1, g: 2, b: 3 }));
+    assert!("gray(0.25)".parse::<Color>() == Ok(Color::Gray(0.25)));
+    assert!("{a b}".parse::<Color>() == Ok(Color::Named { name: "a b".into() }));
+    assert!("-2..-1".parse::<Color>() == Ok(Color::Pair(-1, -2)));
+    assert!("rgb(1,2,300)".parse::<Color>() == Err(ParseError));
+    assert!("rgb(1,2,3".parse::<Color>() == Err(ParseError));
+    assert!("rgb(1,2)".parse::<Color>() == Err(ParseError));
+    assert!("blue".parse::<Color>() == Err(ParseError));
+
+    #[indiscriminant_str(rename_all = "lowercase", Default = "none")]
+    #[derive(PartialEq)]
+    pub enum Shape {
+        Point,
+        Circle { radius: u32 } = "circle:{radius}",
+    }
+
+    assert!(Shape::Point.to_string() == "point");
+    assert!(Shape::Default.to_string() == "none");
+    assert!("point".parse::<Shape>() == Ok(Shape::Point));
+    assert!("circle:4".parse::<Shape>() == Ok(Shape::Circle { radius: 4 }));
+    assert!("circle:x".parse::<Shape>() == Ok(Shape::Default));
+
+    // Generics and variant attributes are kept
+    #[indiscriminant_str()]
+    #[derive(Debug, PartialEq)]
+    pub enum Wrap<T: std::str::FromStr> {
+        Plain = "plain",
+        /// Any value that parses
+        Val(T) = "val({0})",
+    }
+
+    assert!(Wrap::Val(3u8).to_string() == "val(3)");
+    assert!(Wrap::<u8>::Plain.to_string() == "plain");
+    assert!("val(12)".parse::<Wrap<u8>>() == Ok(Wrap::Val(12)));
+    assert!("val(x)".parse::<Wrap<u8>>() == Err(ParseError));
+    assert!("val(x)".parse::<Wrap<String>>() == Ok(Wrap::Val("x".into())));
+}
+
+#[test]
+fn test_payload() {
+    #[indiscriminant_bits(u8)]
+    #[derive(PartialEq)]
+    pub enum Message<'a, T: Clone> {
+        Ping = 1,
+        Data(Vec<u8>) = 2,
+        Borrowed { bytes: &'a [u8], extra: T } = 0x10,
+    }
+
+    let message: Message<u32> = Message::Data(vec![1, 2, 3]);
+    assert!(message.tag() == 2);
+    assert!(message.kind() == MessageKind::Data);
+    assert!(Message::<u32>::Ping.tag() == 1);
+
+    let borrowed = Message::Borrowed {
+        bytes: &[1, 2],
+        extra: 'x',
+    };
+    assert!(borrowed.tag() == 0x10);
+    assert!(borrowed.kind() == MessageKind::Borrowed);
+
+    assert!(MessageKind::from_int(2) == Some(MessageKind::Data));
+    assert!(MessageKind::from_int(3).is_none());
+    assert!(MessageKind::Borrowed.to_int() == 0x10);
+
+    #[indiscriminant_bits(u16, 4, Default)]
+    #[derive(PartialEq)]
+    pub enum Command {
+        Reset = 0,
+        Write { address: u16, value: u8 } = 1,
+    }
+
+    assert!(
+        Command::Write {
+            address: 0,
+            value: 0
+        }
+        .kind()
+            == CommandKind::Write
+    );
+    assert!(CommandKind::from_int(0x31) == CommandKind::Write);
+    assert!(CommandKind::from_int(7) == CommandKind::Default);
+    assert!(Command::Reset.tag() == 0);
+}
+
+#[test]
+fn test_nested() {
+    #[indiscriminant_bits(u8, 4)]
+    #[derive(PartialEq)]
+    pub enum AluOp {
+        Add = 0,
+        Sub = 1,
+        And = 2,
+        Or = 3,
+    }
+
+    #[indiscriminant_bits(u8, 3, Default)]
+    #[derive(PartialEq)]
+    pub enum MemOp {
+        Load = 0,
+        Store = 1,
+    }
+
+    #[indiscriminant_bits(u8, 6)]
+    #[derive(PartialEq)]
+    pub enum Opcode {
+        Nop = 0,
+        #[nested]
+        Alu(AluOp) = 0b01,
+        #[nested]
+        Mem(MemOp) = 0b10,
+    }
+
+    assert!(Opcode::Nop.to_int() == 0);
+    assert!(Opcode::Alu(AluOp::Sub).to_int() == 0b01_0001);
+    assert!(Opcode::Mem(MemOp::Store).to_int() == 0b10_0001);
+    assert!(Opcode::Mem(MemOp::Default).to_int() == 0b10_0010);
+
+    assert!(Opcode::from_int(0) == Some(Opcode::Nop));
+    assert!(Opcode::from_int(0b01_0011) == Some(Opcode::Alu(AluOp::Or)));
+    assert!(Opcode::from_int(0b01_0100).is_none());
+    assert!(Opcode::from_int(0b10_0000) == Some(Opcode::Mem(MemOp::Load)));
+    assert!(Opcode::from_int(0b10_0111) == Some(Opcode::Mem(MemOp::Default)));
+    assert!(Opcode::from_int(0b11_0000).is_none());
+    assert!(Opcode::from_int(0b1101_0010) == Some(Opcode::Alu(AluOp::And)));
+
+    // Nested enums can themselves be nested
+    #[indiscriminant_bits(u16, 7)]
+    #[derive(PartialEq)]
+    pub enum Instruction {
+        #[nested]
+        Short(Opcode) = 0,
+        Halt = 1,
+    }
+
+    assert!(Instruction::Short(Opcode::Alu(AluOp::Or)).to_int() == 0b001_0011);
+    assert!(Instruction::Halt.to_int() == 0b100_0000);
+    assert!(
+        Instruction::from_int(0b010_0001) == Some(Instruction::Short(Opcode::Mem(MemOp::Store)))
+    );
+    assert!(Instruction::from_int(0b100_0000) == Some(Instruction::Halt));
+
+    // The tag takes every bit above the widest nested enum, whatever the tags
+    #[indiscriminant_bits(u8, 8)]
+    #[derive(PartialEq)]
+    pub enum Wide {
+        Nop = 0,
+        #[nested]
+        Alu(AluOp) = 1,
+    }
+
+    assert!(Wide::Alu(AluOp::Sub).to_int() == 0b0001_0001);
+    assert!(Wide::from_int(0b0001_0011) == Some(Wide::Alu(AluOp::Or)));
+    assert!(Wide::from_int(0b0000_0000) == Some(Wide::Nop));
+
+    // from_int only accepts what to_int produces
+    assert!(Wide::from_int(0b0000_0011).is_none());
+    assert!(Opcode::from_int(0b00_0001).is_none());
+    assert!(Opcode::from_int(0b10_1000).is_none());
+    assert!(Opcode::from_int(0b10_0100) == Some(Opcode::Mem(MemOp::Default)));
+}
+
+#[test]
+fn test_iteration() {
+    #[indiscriminant_bits(u8, 2, Default)]
+    #[derive(PartialEq)]
+    pub enum Mixed {
+        D = 3,
+        A = 0,
+        B = 1,
+    }
+
+    const { assert!(Mixed::COUNT == 4) };
+    assert!(Mixed::ALL == [Mixed::A, Mixed::B, Mixed::Default, Mixed::D]);
+    assert!(Mixed::iter().map(|v| v.to_int()).eq([0, 1, 2, 3]));
+    assert!(Mixed::MIN == Mixed::A);
+    assert!(Mixed::MAX == Mixed::D);
+
+    assert!(Mixed::A.next() == Some(Mixed::B));
+    assert!(Mixed::B.next() == Some(Mixed::Default));
+    assert!(Mixed::D.next().is_none());
+    assert!(Mixed::A.prev().is_none());
+    assert!(Mixed::D.prev() == Some(Mixed::Default));
+    assert!(Mixed::D.cycle_next() == Mixed::A);
+    assert!(Mixed::B.cycle_next() == Mixed::Default);
+
+    #[indiscriminant_bits(u8, 4)]
+    #[derive(PartialEq)]
+    pub enum Sparse {
+        A = 2,
+        B = 8,
+    }
+
+    const { assert!(Sparse::VALID_MASK == 0b1111) };
+    assert!(Sparse::is_valid(2));
+    assert!(Sparse::is_valid(8));
+    assert!(!Sparse::is_valid(3));
+    assert!(!Sparse::is_valid(0x12));
+    assert!(Sparse::ALL == [Sparse::A, Sparse::B]);
+
+    #[indiscriminant_str(Default = "?")]
+    #[derive(PartialEq)]
+    pub enum Word {
+        Zeta = "z",
+        Alpha = "a",
+    }
+
+    const { assert!(Word::COUNT == 3) };
+    assert!(Word::ALL == [Word::Zeta, Word::Alpha, Word::Default]);
+    assert!(Word::iter().map(|w| w.to_str()).eq(["z", "a", "?"]));
+    assert!(Word::Alpha.next() == Some(Word::Default));
+    assert!(Word::Default.cycle_next() == Word::Zeta);
+
+    #[indiscriminant_byte_str()]
+    #[derive(PartialEq)]
+    pub enum Bytes {
+        One = b"1",
+    }
+
+    assert!(Bytes::ALL == [Bytes::One]);
+    assert!(Bytes::One.next().is_none());
+    assert!(Bytes::One.prev().is_none());
+    assert!(Bytes::One.cycle_next() == Bytes::One);
+}
+
+#[test]
+fn test_raw_constants() {
+    #[indiscriminant_bits(u8, 4, Default = 15)]
+    pub enum Command {
+        Read = 1,
+        WriteBack = 9,
+    }
+
+    const fn command_len(raw: u8) -> usize {
+        match raw & 0b1111 {
+            Command::RAW_READ => 1,
+            Command::RAW_WRITE_BACK => 2,
+            _ => 0,
+        }
+    }
+    const { assert!(command_len(0x19) == 2) };
+    const { assert!(Command::RAW_DEFAULT == 15) };
+    assert!(command_len(1) == 1);
+    assert!(command_len(Command::Default.to_int()) == 0);
+
+    #[indiscriminant_str(Default = "unknown")]
+    pub enum Method {
+        Get = "GET",
+        Post = "POST",
+    }
+
+    let is_get = |value: &str| matches!(value, Method::RAW_GET);
+    assert!(is_get("GET"));
+    assert!(!is_get("POST"));
+    assert!(Method::RAW_DEFAULT == "unknown");
+
+    #[indiscriminant_byte_str()]
+    pub enum Magic {
+        Elf = b"\x7fELF",
+    }
+
+    assert!(matches!(&b"\x7fELF"[..], Magic::RAW_ELF));
+}
+
+#[test]
+fn test_const_conversions() {
+    #[indiscriminant_bits(u8, 4)]
+    #[derive(PartialEq)]
+    pub enum Size {
+        Small = 2,
+        Large = 8,
+    }
+
+    const LARGE: u8 = Size::Large.to_int();
+    const SMALL: Option<Size> = Size::from_int(0x12);
+    let table = [0u8; LARGE as usize];
+    assert!(table.len() == 8);
+    assert!(SMALL == Some(Size::Small));
+    const { assert!(Size::from_int(3).is_none()) };
+
+    #[indiscriminant_str(Default = "other")]
+    #[derive(PartialEq)]
+    pub enum Color {
+        Red = "red",
+        #[glob("gr?y")]
+        Grey = "grey",
+    }
+
+    const NAMES: [&str; 2] = [Color::Red.to_str(), Color::Default.to_str()];
+    const GRAY: Color = Color::from_str("gray");
+    assert!(NAMES == ["red", "other"]);
+    assert!(GRAY == Color::Grey);
+    assert!(Color::from_str("r") == Color::Default);
+    assert!(Color::from_str("rede") == Color::Default);
+
+    #[indiscriminant_byte_str()]
+    #[derive(PartialEq)]
+    pub enum Magic {
+        Gzip = b"\x1f\x8b",
+        Png = b"\x89PNG",
+    }
+
+    const PNG_LEN: usize = Magic::Png.to_byte_str().len();
+    const GZIP: Option<Magic> = Magic::from_byte_str(b"\x1f\x8b");
+    const { assert!(PNG_LEN == 4) };
+    assert!(GZIP == Some(Magic::Gzip));
+    assert!(Magic::from_byte_str(b"\x1f").is_none());
+}
+
+#[test]
+fn test_lookup_table() {
+    #[indiscriminant_bits(u8, 6)]
+    #[derive(PartialEq)]
+    pub enum Dense {
+        V0 = 0,
+        V1 = 1,
+        V3 = 3,
+        V4 = 4,
+        V6 = 6,
+        V7 = 7,
+        V9 = 9,
+        V10 = 10,
+        V12 = 12,
+        V13 = 13,
+        V15 = 15,
+        V16 = 16,
+        V18 = 18,
+        V19 = 19,
+        V21 = 21,
+        V22 = 22,
+        V24 = 24,
+        V25 = 25,
+        V27 = 27,
+        V28 = 28,
+        V30 = 30,
+        V31 = 31,
+        V33 = 33,
+        V34 = 34,
+        V36 = 36,
+        V37 = 37,
+        V39 = 39,
+        V40 = 40,
+        V42 = 42,
+        V43 = 43,
+    }
+
+    for value in 0..=u8::MAX {
+        let decoded = Dense::from_int(value);
+        let masked = value & 0b11_1111;
+        if masked % 3 == 2 || masked > 43 {
+            assert!(decoded.is_none());
+        } else {
+            assert!(decoded.map(|v| v.to_int()) == Some(masked));
+        }
+    }
+    const { assert!(Dense::from_int(0x40).is_some()) };
+    const { assert!(Dense::from_int(5).is_none()) };
+}
+
+#[test]
+fn test_dispatch() {
+    #[indiscriminant_str(Default = "?")]
+    #[derive(PartialEq)]
+    pub enum Keyword {
+        Empty = "",
+        As = "as",
+        At = "at",
+        By = "by",
+        Set = "set",
+        Sum = "sum",
+        Select = "select",
+        Update = "update",
+        Upsert = "upsert",
+    }
+
+    for keyword in Keyword::iter() {
+        assert!(Keyword::from_str(keyword.to_str()) == keyword);
+    }
+    assert!(Keyword::from_str("a") == Keyword::Default);
+    assert!(Keyword::from_str("ax") == Keyword::Default);
+    assert!(Keyword::from_str("selects") == Keyword::Default);
+    const { assert!(matches!(Keyword::from_str("upsert"), Keyword::Upsert)) };
+
+    #[indiscriminant_byte_str()]
+    #[derive(PartialEq)]
+    pub enum Opcode {
+        Nop = b"\x00",
+        Push = b"\x01\x00",
+        Pop = b"\x01\x01",
+        Dup = b"\x02",
+        Swap = b"\x03",
+        Jump = b"\x04\x00\x00",
+        Call = b"\x04\x00\x01",
+        Ret = b"\x05",
+    }
+
+    for opcode in Opcode::iter() {
+        assert!(Opcode::from_byte_str(opcode.to_byte_str()) == Some(opcode));
+    }
+    assert!(Opcode::from_byte_str(b"").is_none());
+    assert!(Opcode::from_byte_str(b"\x01").is_none());
+    assert!(Opcode::from_byte_str(b"\x04\x00\x02").is_none());
+}
+
+#[test]
+fn test_unchecked() {
+    #[indiscriminant_bits(u16, 12, Default = 7)]
+    #[derive(PartialEq)]
+    pub enum Register {
+        Zero = 0,
+        Status = 0x100,
+        Control = 0xfff,
+    }
+
+    for register in Register::iter() {
+        let raw = register.to_int();
+        assert!(unsafe { Register::from_int_unchecked(raw) } == register);
+    }
+    const STATUS: u16 = Register::Status.to_int();
+    const CONTROL: Register = unsafe { Register::from_int_unchecked(0xfff) };
+    const { assert!(STATUS == 0x100) };
+    assert!(CONTROL == Register::Control);
+    assert!(Register::Default.to_int() == 7);
+
+    #[indiscriminant_bits(u8, 1)]
+    #[derive(PartialEq)]
+    pub enum Bit {
+        Low = 0,
+        High = 1,
+    }
+
+    assert!(unsafe { Bit::from_int_unchecked(Bit::High.to_int()) } == Bit::High);
+}
+
+#[test]
+#[should_panic]
+#[cfg(debug_assertions)]
+fn test_unchecked_invalid() {
+    #[indiscriminant_bits(u8, 4)]
+    pub enum Sparse {
+        A = 1,
+        B = 4,
+    }
+
+    let _ = unsafe { Sparse::from_int_unchecked(2) };
+}
+
+#[test]
+fn test_packed_vec() {
+    #[indiscriminant_bits(u8, 2)]
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum Base {
+        A = 0,
+        C = 1,
+        G = 2,
+        T = 3,
+    }
+
+    let mut bases = PackedVec::new();
+    for base in [Base::G, Base::A, Base::T, Base::T, Base::C] {
+        bases.push(base);
+    }
+    assert!(bases.len() == 5);
+    assert!(bases.as_bytes() == [0b1111_0010, 0b01]);
+    assert!(bases.get(2) == Some(Base::T));
+    assert!(bases.get(5).is_none());
+    bases.set(0, Base::C);
+    assert!(bases
+        .iter()
+        .eq([Base::C, Base::A, Base::T, Base::T, Base::C]));
+    assert!(bases.pop() == Some(Base::C));
+    assert!(bases.as_bytes() == [0b1111_0001]);
+
+    let copy = PackedVec::<Base>::from_bytes(bases.clone().into_bytes(), 4).unwrap();
+    assert!(copy == bases);
+    assert!(format!("{:?}", copy) == "[C, A, T, T]");
+
+    // Elements of odd widths straddle byte boundaries
+    #[indiscriminant_bits(u8, 3)]
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum Class {
+        Sky = 1,
+        Road = 4,
+        Tree = 6,
+    }
+
+    let classes: PackedVec<Class> = [Class::Tree, Class::Sky, Class::Road, Class::Tree]
+        .into_iter()
+        .collect();
+    assert!(classes.as_bytes() == [0b0000_1110, 0b0000_1101]);
+    let slice = classes.as_slice();
+    assert!(slice.get(3) == Some(Class::Tree));
+    assert!(slice.iter().len() == 4);
+
+    let bytes = classes.as_bytes();
+    assert!(PackedSlice::<Class>::from_bytes(bytes, 4).unwrap().to_vec() == classes);
+    assert!(PackedSlice::<Class>::from_bytes(bytes, 3).is_none());
+    assert!(PackedSlice::<Class>::from_bytes(&[0b000], 1).is_none());
+    assert!(PackedSlice::<Class>::from_bytes(&[0b1000_0001], 1).is_none());
+}
+
+#[test]
+fn test_bitstream() {
+    #[indiscriminant_bits(u8, 3)]
+    #[derive(Debug, PartialEq)]
+    pub enum Version {
+        V1 = 1,
+        V2 = 2,
+    }
+
+    #[indiscriminant_bits(u8, 5, Default)]
+    #[derive(Debug, PartialEq)]
+    pub enum Kind {
+        Data = 0,
+        Ack = 17,
+    }
+
+    let mut writer = BitWriter::new(BitOrder::MsbFirst);
+    writer.write(&Version::V2);
+    writer.write(&Kind::Ack);
+    writer.write_bits(0xabc, 12);
+    assert!(writer.position() == 20);
+    assert!(writer.as_bytes() == [0b0101_0001, 0xab, 0xc0]);
+
+    let bytes = writer.into_bytes();
+    let mut reader = BitReader::new(&bytes, BitOrder::MsbFirst);
+    assert!(reader.read::<Version>() == Ok(Version::V2));
+    assert!(reader.read::<Kind>() == Ok(Kind::Ack));
+    assert!(reader.read_bits(12) == Ok(0xabc));
+    assert!(reader.remaining() == 4);
+    assert!(reader.read::<Kind>() == Err(ReadError::Truncated));
+    assert!(reader.position() == 20);
+
+    let mut writer = BitWriter::new(BitOrder::LsbFirst);
+    writer.write(&Version::V1);
+    writer.write(&Kind::Ack);
+    writer.write_bits(0xabc, 12);
+    assert!(writer.as_bytes() == [0b1000_1001, 0xbc, 0x0a]);
+
+    let bytes = writer.into_bytes();
+    let mut reader = BitReader::new(&bytes, BitOrder::LsbFirst);
+    assert!(reader.read::<Version>() == Ok(Version::V1));
+    assert!(reader.read::<Kind>() == Ok(Kind::Ack));
+    assert!(reader.read_bits(12) == Ok(0xabc));
+
+    // Invalid values leave the reader where it was
+    let mut reader = BitReader::new(&[0b1110_0000], BitOrder::MsbFirst);
+    assert!(reader.read::<Version>() == Err(ReadError::InvalidValue(0b111)));
+    assert!(reader.position() == 0);
+    assert!(reader.read_bits(3) == Ok(0b111));
+}
+
+#[test]
+fn test_byte_order() {
+    #[indiscriminant_bits(u16, 12)]
+    #[derive(Debug, PartialEq)]
+    pub enum EtherType {
+        Ipv4 = 0x800,
+        Arp = 0x806,
+    }
+
+    const ARP: [u8; 2] = EtherType::Arp.to_be_bytes();
+    assert!(ARP == [0x08, 0x06]);
+    assert!(EtherType::Ipv4.to_le_bytes() == [0x00, 0x08]);
+    assert!(EtherType::from_be_bytes([0x08, 0x00]) == Some(EtherType::Ipv4));
+    assert!(EtherType::from_le_bytes([0x06, 0x08]) == Some(EtherType::Arp));
+    assert!(EtherType::from_be_bytes([0x08, 0x01]).is_none());
+
+    // Cursor reads advance past each value
+    let mut buf = Vec::new();
+    EtherType::Arp.put(&mut buf, ByteOrder::BigEndian);
+    EtherType::Ipv4.put(&mut buf, ByteOrder::LittleEndian);
+    assert!(buf == [0x08, 0x06, 0x00, 0x08]);
+    let mut cursor = &buf[..];
+    assert!(EtherType::get(&mut cursor, ByteOrder::BigEndian) == Ok(EtherType::Arp));
+    assert!(cursor.len() == 2);
+    assert!(EtherType::get(&mut cursor, ByteOrder::BigEndian) == Err(ReadError::InvalidValue(8)));
+    assert!(EtherType::get(&mut cursor, ByteOrder::LittleEndian) == Ok(EtherType::Ipv4));
+    assert!(EtherType::get(&mut cursor, ByteOrder::LittleEndian) == Err(ReadError::Truncated));
+
+    // Readers and writers from std::io
+    #[cfg(feature = "std")]
+    {
+        let mut output = Vec::new();
+        EtherType::Ipv4
+            .write_to(&mut output, ByteOrder::BigEndian)
+            .unwrap();
+        assert!(output == [0x08, 0x00]);
+        let mut input = &output[..];
+        assert!(EtherType::read_from(&mut input, ByteOrder::BigEndian).unwrap() == EtherType::Ipv4);
+        let error = EtherType::read_from(&mut input, ByteOrder::BigEndian).unwrap_err();
+        assert!(error.kind() == std::io::ErrorKind::UnexpectedEof);
+        let error = EtherType::read_from(&mut &[0, 0][..], ByteOrder::BigEndian).unwrap_err();
+        assert!(error.kind() == std::io::ErrorKind::InvalidData);
+    }
+}
+
+#[test]
+fn test_varint() {
+    #[indiscriminant_bits(u16)]
+    #[derive(Debug, PartialEq)]
+    pub enum Section {
+        Custom = 0,
+        Type = 1,
+        Large = 300,
+    }
+
+    let mut buf = [0; 3];
+    assert!(Section::Custom.encoded_len() == 1);
+    assert!(Section::Large.encoded_len() == 2);
+    assert!(Section::Large.encode_varint(&mut buf) == 2);
+    assert!(buf[..2] == [0xac, 0x02]);
+    assert!(Section::decode_varint(&[0xac, 0x02, 0xff]) == Ok((Section::Large, 2)));
+    assert!(Section::decode_varint(&[0x00]) == Ok((Section::Custom, 1)));
+
+    assert!(Section::decode_varint(&[0x81, 0x00]) == Err(ReadError::Overlong));
+    assert!(Section::decode_varint(&[0x80, 0x80, 0x00]) == Err(ReadError::Overlong));
+    assert!(Section::decode_varint(&[0xac]) == Err(ReadError::Truncated));
+    assert!(Section::decode_varint(&[0x80, 0x80, 0x04]) == Err(ReadError::Overflow));
+    assert!(Section::decode_varint(&[0x02]) == Err(ReadError::InvalidValue(2)));
+
+    // Enums with a default decode unknown values to it
+    #[indiscriminant_bits(u8, Default)]
+    #[derive(Debug, PartialEq)]
+    pub enum Tag {
+        Varint = 0,
+        Fixed = 1,
+    }
+
+    assert!(Tag::decode_varint(&[0x7f]) == Ok((Tag::Default, 1)));
+    assert!(Tag::Default.encode_varint(&mut buf) == 1);
+    assert!(buf[0] == 2);
+
+    // Signed LEB128 reads the bit-width as two's complement
+    #[indiscriminant_bits(u8, 7)]
+    #[derive(Debug, PartialEq)]
+    pub enum ValType {
+        I32 = 0x7f,
+        I64 = 0x7e,
+        Empty = 0x40,
+        Small = 0x3f,
+    }
+
+    assert!(ValType::I32.encoded_signed_len() == 1);
+    assert!(ValType::I32.encode_signed_varint(&mut buf) == 1);
+    assert!(buf[0] == 0x7f);
+    assert!(ValType::Empty.encode_signed_varint(&mut buf) == 1);
+    assert!(buf[0] == 0x40);
+    assert!(ValType::decode_signed_varint(&[0x7e, 0xff]) == Ok((ValType::I64, 1)));
+    assert!(ValType::decode_signed_varint(&[0x3f]) == Ok((ValType::Small, 1)));
+
+    assert!(ValType::decode_signed_varint(&[0xff, 0x7f]) == Err(ReadError::Overlong));
+    assert!(ValType::decode_signed_varint(&[0xbf, 0x00]) == Err(ReadError::Overlong));
+    assert!(ValType::decode_signed_varint(&[0xff]) == Err(ReadError::Truncated));
+    assert!(ValType::decode_signed_varint(&[0xc0, 0x00]) == Err(ReadError::Overflow));
+    assert!(ValType::decode_signed_varint(&[0x80, 0x7f]) == Err(ReadError::Overflow));
+    assert!(ValType::decode_signed_varint(&[0x7c]) == Err(ReadError::InvalidValue(0x7c)));
+
+    // Values needing more than one group, up to the sign bit of a u32
+    assert!(Section::Large.encoded_signed_len() == 2);
+    assert!(Section::Large.encode_signed_varint(&mut buf) == 2);
+    assert!(Section::decode_signed_varint(&buf[..2]) == Ok((Section::Large, 2)));
+
+    #[indiscriminant_bits(u32, 32)]
+    #[derive(Debug, PartialEq)]
+    pub enum Wide {
+        Zero = 0,
+        Min = 0x8000_0000,
+    }
+
+    let mut buf = [0; 5];
+    assert!(Wide::Min.encode_signed_varint(&mut buf) == 5);
+    assert!(buf == [0x80, 0x80, 0x80, 0x80, 0x78]);
+    assert!(Wide::decode_signed_varint(&buf) == Ok((Wide::Min, 5)));
+    assert!(
+        Wide::decode_signed_varint(&[0x80, 0x80, 0x80, 0x80, 0x70]) == Err(ReadError::Overflow)
+    );
+    assert!(
+        Wide::decode_signed_varint(&[0x80, 0x80, 0x80, 0x80, 0x08]) == Err(ReadError::Overflow)
+    );
+    assert!(Wide::decode_signed_varint(&[0x00]) == Ok((Wide::Zero, 1)));
+}
+
+#[test]
+fn test_code() {
+    #[indiscriminant_code()]
+    #[derive(Debug, PartialEq)]
+    pub enum Symbol {
+        A = "0",
+        B = "10",
+        C = "110",
+        D = "1110",
+    }
+
+    assert!(Symbol::A.code() == (0b0, 1));
+    assert!(Symbol::C.code() == (0b110, 3));
+    const { assert!(Symbol::MAX_CODE_LEN == 4) };
+
+    for order in [BitOrder::MsbFirst, BitOrder::LsbFirst] {
+        let mut writer = BitWriter::new(order);
+        for symbol in [Symbol::C, Symbol::A, Symbol::D, Symbol::B, Symbol::A] {
+            symbol.encode(&mut writer);
+        }
+        assert!(writer.position() == 11);
+
+        let bytes = writer.into_bytes();
+        let mut reader = BitReader::new(&bytes, order);
+        let mut decoded = Vec::new();
+        while decoded.len() < 5 {
+            decoded.push(Symbol::decode(&mut reader).unwrap());
+        }
+        assert!(decoded == [Symbol::C, Symbol::A, Symbol::D, Symbol::B, Symbol::A]);
+    }
+
+    // The input ends partway through a third code
+    let mut reader = BitReader::new(&[0b0111_0111], BitOrder::MsbFirst);
+    assert!(Symbol::decode(&mut reader) == Ok(Symbol::A));
+    assert!(Symbol::decode(&mut reader) == Ok(Symbol::D));
+    assert!(Symbol::decode(&mut reader) == Err(ReadError::Truncated));
+    assert!(reader.position() == 5);
+
+    // The code 1111 is left unused
+    let mut reader = BitReader::new(&[0b1111_0000], BitOrder::MsbFirst);
+    assert!(Symbol::decode(&mut reader) == Err(ReadError::InvalidValue(0b1111)));
+    assert!(reader.position() == 0);
+}
+
+#[test]
+fn test_encoding() {
+    #[indiscriminant_bits(u8, 3, encoding = gray)]
+    #[derive(Debug, PartialEq)]
+    pub enum Position {
+        P0 = 0,
+        P1 = 1,
+        P2 = 2,
+        P3 = 3,
+        P4 = 4,
+    }
+
+    assert!(Position::iter()
+        .map(|p| p.to_int())
+        .eq([0b000, 0b001, 0b011, 0b010, 0b110]));
+    assert!(Position::from_int(0b011) == Some(Position::P2));
+    assert!(Position::from_int(0b111).is_none());
+    const { assert!(Position::RAW_P3 == 0b010) };
+    assert!(Position::MAX == Position::P4);
+
+    #[indiscriminant_bits(u8, 4, encoding = one_hot)]
+    #[derive(Debug, PartialEq)]
+    pub enum State {
+        Idle = 0,
+        Busy = 1,
+        Done = 3,
+    }
+
+    assert!(State::Done.to_int() == 0b1000);
+    assert!(State::from_int(0b0010) == Some(State::Busy));
+    assert!(State::from_int(0b0000).is_none());
+    assert!(State::from_int(0b0011).is_none());
+    assert!(State::is_valid(0b0001));
+    assert!(!State::is_valid(0b0100));
+    assert!(unsafe { State::from_int_unchecked(0b1000) } == State::Done);
+
+    #[indiscriminant_bits(u8, 4, Default, encoding = thermometer)]
+    #[derive(Debug, PartialEq)]
+    pub enum Level {
+        Empty = 0,
+        Low = 1,
+        Full = 4,
+    }
+
+    assert!(Level::Full.to_int() == 0b1111);
+    assert!(Level::Default.to_int() == 0b0011);
+    assert!(Level::from_int(0b0001) == Level::Low);
+    assert!(Level::from_int(0b0101) == Level::Default);
+
+    #[indiscriminant_bits(u8, encoding = bcd)]
+    #[derive(Debug, PartialEq)]
+    pub enum Channel {
+        Nine = 9,
+        TwentyThree = 23,
+        NinetyNine = 99,
+    }
+
+    assert!(Channel::TwentyThree.to_int() == 0x23);
+    assert!(Channel::from_be_bytes([0x99]) == Some(Channel::NinetyNine));
+    assert!(Channel::from_int(0x0a).is_none());
+    assert!(Channel::ALL == [Channel::Nine, Channel::TwentyThree, Channel::NinetyNine]);
+}
+
+#[test]
+fn test_flags() {
+    #[indiscriminant_flags(u32)]
+    #[derive(Debug, PartialEq)]
+    pub enum Permission {
+        Read = 0b0001,
+        Write = 0b0010,
+        Exec = 0b0100,
+        SetUid = 0b1000_0000,
+    }
+
+    let mut flags = Permission::Read | Permission::Exec;
+    assert!(flags.bits() == 0b0101);
+    assert!(flags.contains(Permission::Read.into()));
+    assert!(!flags.contains(Permission::Read | Permission::Write));
+    assert!(flags.intersects(Permission::Read | Permission::Write));
+    assert!(flags.iter().eq([Permission::Read, Permission::Exec]));
+
+    flags |= Permission::Write;
+    flags.remove(Permission::Read);
+    assert!(flags == Permission::Write | Permission::Exec);
+    assert!((flags & !PermissionFlags::from(Permission::Exec)) == Permission::Write.into());
+    assert!(PermissionFlags::ALL.bits() == 0b1000_0111);
+    assert!(PermissionFlags::EMPTY.is_empty());
+    assert!([Permission::Exec, Permission::SetUid]
+        .into_iter()
+        .collect::<PermissionFlags>()
+        .contains(Permission::SetUid.into()));
+
+    // Display and FromStr
+    assert!(flags.to_string() == "WRITE | EXEC");
+    assert!(PermissionFlags::EMPTY.to_string() == "0x0");
+    assert!(Permission::SetUid | Permission::Read == "READ | SET_UID".parse().unwrap());
+    assert!("READ | WRITE |".parse::<PermissionFlags>().is_err());
+    assert!("READ | EXECUTE".parse::<PermissionFlags>().is_err());
+    assert!(format!("{:?}", flags) == "PermissionFlags(WRITE | EXEC)");
+
+    // Unknown bits round-trip
+    let register = PermissionFlags::from_bits(0x1_0301);
+    assert!(register.unknown_bits() == 0x1_0300);
+    assert!(register.to_string() == "READ | 0x10300");
+    assert!(register.to_string().parse::<PermissionFlags>() == Ok(register));
+    assert!(register.iter().eq([Permission::Read]));
+
+    // Flags may use every bit of a u64
+    #[indiscriminant_flags(u64)]
+    #[derive(Debug, PartialEq)]
+    pub enum Status {
+        Ready = 0x1,
+        Fault = 0x8000_0000_0000_0000,
+    }
+
+    let status = Status::Ready | Status::Fault;
+    assert!(status.bits() == 0x8000_0000_0000_0001);
+    assert!(status.to_string() == "READY | FAULT");
+    assert!(StatusFlags::from_bits(0x4000_0000_0000_0000).unknown_bits() == 0x4000_0000_0000_0000);
+    assert!(Status::from_int(0x8000_0000_0000_0000) == Some(Status::Fault));
+}